@@ -0,0 +1,51 @@
+//! Conversion and register encoding benchmarks.
+//!
+//! Run with `cargo bench`, comparing against a baseline with `-- --save-baseline <name>` and
+//! `-- --baseline <name>`.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ds4432::mock::{MockBus, MockDs4432};
+use ds4432::{Output, Status};
+
+fn status(c: &mut Criterion) {
+    c.bench_function("status_from_register", |b| {
+        b.iter(|| Status::from(black_box(0xAA)))
+    });
+    c.bench_function("register_from_status", |b| {
+        b.iter(|| u8::try_from(black_box(Status::Source(42))))
+    });
+    c.bench_function("code_with_rfs", |b| {
+        b.iter(|| black_box(Status::SinkMicroAmp(88.0)).code_with_rfs(black_box(80_000)))
+    });
+    c.bench_function("current_ua", |b| {
+        b.iter(|| black_box(Status::Sink(112)).current_ua(black_box(80_000)))
+    });
+    c.bench_function("sink_ua", |b| {
+        b.iter(|| Status::sink_ua(black_box(80_000), black_box(88.0)))
+    });
+    c.bench_function("lerp", |b| {
+        b.iter(|| {
+            Status::lerp(
+                black_box(Status::Sink(100)),
+                black_box(Status::Source(100)),
+                black_box(0.3),
+            )
+        })
+    });
+}
+
+fn driver(c: &mut Criterion) {
+    let mut ds4432 = MockDs4432::with_rfs(MockBus::new(), Some(80_000), None).unwrap();
+    c.bench_function("set_status_code", |b| {
+        b.iter(|| ds4432.set_status(Output::Zero, black_box(Status::Source(42))))
+    });
+    c.bench_function("set_status_ua", |b| {
+        b.iter(|| ds4432.set_status(Output::Zero, black_box(Status::SourceMicroAmp(88.0))))
+    });
+    c.bench_function("status_ua", |b| b.iter(|| ds4432.status(Output::Zero)));
+}
+
+criterion_group!(benches, status, driver);
+criterion_main!(benches);
@@ -0,0 +1,20 @@
+#!/bin/sh
+# Report the code size of the driver for representative feature combinations.
+#
+# Usage: size/report.sh [target], the target defaulting to thumbv6m-none-eabi (Cortex-M0) and
+# having to be installed with `rustup target add`.
+#
+# Only the code of the probe (the driver after LTO) is counted, not the compiler-builtins
+# routines, such as the soft-float ones, the firmware would link.
+set -e
+
+target=${1:-thumbv6m-none-eabi}
+cd "$(dirname "$0")"
+
+printf '%-16s %8s %8s\n' features text data
+for features in sync sync,tiny sync,log; do
+    cargo build -q --release --target "$target" --no-default-features --features "$features"
+    llvm-size "target/$target/release/libds4432_size.a" |
+        awk -v features="$features" \
+            '$6 ~ /^ds4432_size-/ { printf "%-16s %8d %8d\n", features, $1, $2 }'
+done
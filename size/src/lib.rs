@@ -0,0 +1,65 @@
+//! Code size probe.
+//!
+//! Exports the typical uses of the driver as C functions so they are kept in the static library
+//! measured by `report.sh`.
+
+#![no_std]
+
+use ds4432::{Output, Status, DS4432};
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, Operation};
+
+/// A bus touching the transferred bytes so they aren't optimized out.
+struct Bus;
+
+impl ErrorType for Bus {
+    type Error = ErrorKind;
+}
+
+impl I2c for Bus {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => {
+                    core::hint::black_box((address, &bytes));
+                }
+                Operation::Read(buf) => buf.fill(core::hint::black_box(address)),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn ds4432_set_code(code: u8) -> bool {
+    let mut ds4432 = DS4432::new(Bus);
+    ds4432
+        .set_status(Output::Zero, Status::Source(code))
+        .is_ok()
+}
+
+#[no_mangle]
+pub extern "C" fn ds4432_get_code() -> u8 {
+    let mut ds4432 = DS4432::new(Bus);
+    ds4432
+        .status(Output::One)
+        .ok()
+        .and_then(|status| status.code())
+        .unwrap_or_default()
+}
+
+#[no_mangle]
+pub extern "C" fn ds4432_set_ua(current_ua: f32) -> bool {
+    DS4432::with_rfs(Bus, Some(80_000), None)
+        .and_then(|mut ds4432| ds4432.set_status(Output::Zero, Status::SinkMicroAmp(current_ua)))
+        .is_ok()
+}
+
+#[cfg(not(test))]
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo) -> ! {
+    loop {}
+}
@@ -0,0 +1,21 @@
+//! The async driver.
+//!
+//! A feature-neutral path to [`AsyncDS4432`](crate::AsyncDS4432), for codebases using both the
+//! `blocking` and the async drivers. Both share the register encoding and the
+//! microamps conversions.
+//!
+//! # Example
+//! ```
+//! use ds4432::asynchronous::Ds4432;
+//! use ds4432::{Output, Status};
+//! use embedded_hal_async::i2c::I2c;
+//!
+//! async fn trim<I: I2c>(mut ds4432: Ds4432<I>) {
+//!     ds4432.set_status(Output::Zero, Status::Sink(42)).await.unwrap();
+//! }
+//! ```
+
+use crate::delay::NoDelay;
+
+/// The async DS4432 driver, over an `embedded_hal_async::i2c::I2c` bus.
+pub type Ds4432<I, T = NoDelay> = crate::AsyncDS4432<I, T>;
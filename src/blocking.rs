@@ -0,0 +1,22 @@
+//! The blocking driver.
+//!
+//! A feature-neutral path to [`DS4432`](crate::DS4432), for codebases using both the blocking and
+//! the `asynchronous` drivers. Both share the register encoding and the
+//! microamps conversions.
+//!
+//! # Example
+//! ```
+//! # use embedded_hal_mock::eh1::i2c;
+//! use ds4432::blocking::Ds4432;
+//! use ds4432::{Output, Status};
+//!
+//! # let i2c = i2c::Mock::new(&[i2c::Transaction::write(0x48, vec![0xF8, 0x2A])]);
+//! let mut ds4432 = Ds4432::new(i2c);
+//! ds4432.set_status(Output::Zero, Status::Sink(42)).unwrap();
+//! # ds4432.release().done();
+//! ```
+
+use crate::delay::NoDelay;
+
+/// The blocking DS4432 driver, over an `embedded_hal::i2c::I2c` bus.
+pub type Ds4432<I, T = NoDelay> = crate::DS4432<I, T>;
@@ -0,0 +1,246 @@
+//! Charge accounting.
+//!
+//! A [`ChargeMeter`] integrates the current of each output over time, to budget the bias
+//! current injected over a mission profile on battery-powered designs. Sunk and sourced currents
+//! both add to the charge. Once given a meter with `set_meter` and a clock with `set_clock`, the
+//! driver meters every write to an output with a known Rfs, whatever method issues it. A meter
+//! can also be fed by the application with the currents it commands.
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! # use embedded_hal_mock::eh1::i2c;
+//! use core::sync::atomic::{AtomicU64, Ordering};
+//! use ds4432::charge::ChargeMeter;
+//! use ds4432::time::Clock;
+//! use ds4432::{Output, Status, DS4432};
+//!
+//! // microseconds since boot, e.g. counted by a hardware timer
+//! static UPTIME_US: AtomicU64 = AtomicU64::new(0);
+//!
+//! struct Uptime;
+//!
+//! impl Clock for Uptime {
+//!     fn now_us(&self) -> u64 {
+//!         UPTIME_US.load(Ordering::Relaxed)
+//!     }
+//! }
+//!
+//! # #[cfg(not(feature = "tiny"))] {
+//! # let i2c = i2c::Mock::new(&[
+//! #     i2c::Transaction::write(0x48, vec![0xF8, 0xFF]),
+//! #     i2c::Transaction::write_read(0x48, vec![0xF8], vec![0xFF]),
+//! #     i2c::Transaction::write(0x48, vec![0xF8, 0x00]),
+//! # ]);
+//! let mut ds4432 = DS4432::with_rfs(i2c, Some(80_000), None).unwrap();
+//! ds4432.set_clock(Some(&Uptime));
+//! ds4432.set_meter(Some(ChargeMeter::new()));
+//!
+//! ds4432.set_status(Output::Zero, Status::Source(127)).unwrap();
+//! UPTIME_US.store(1_000_000, Ordering::Relaxed);
+//! ds4432.disable(Output::Zero).unwrap();
+//!
+//! // 98.921µA for 1s, then nothing for 1s
+//! let meter = ds4432.config().meter().unwrap();
+//! assert_eq!(meter.charge_uc(Output::Zero, 2_000_000), 98.921);
+//! assert_eq!(meter.average_ua(Output::Zero, 2_000_000), Some(49.4605));
+//! # ds4432.release().done();
+//! # }
+//! ```
+
+#[cfg(feature = "sync")]
+use crate::DS4432;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::ErrorType;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+use crate::time::InstalledClock;
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+use crate::{Output, PerOutput};
+
+/// The integral of the current of an output since its first record.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+struct Integral {
+    /// Timestamp of the first record
+    start_us: Option<u64>,
+    /// Timestamp of the last record
+    last_us: u64,
+    /// Current since the last record, in nanoamps
+    current_na: u32,
+    /// Charge until the last record, in nA·µs
+    charge: u128,
+}
+
+impl Integral {
+    /// The charge until `now_us`, in nA·µs.
+    fn charge_at(&self, now_us: u64) -> u128 {
+        match self.start_us {
+            Some(_) => {
+                self.charge + self.current_na as u128 * now_us.saturating_sub(self.last_us) as u128
+            }
+            None => 0,
+        }
+    }
+}
+
+/// The charge delivered by each output.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct ChargeMeter {
+    integrals: PerOutput<Integral>,
+}
+
+impl ChargeMeter {
+    /// Create a meter without any charge.
+    pub const fn new() -> Self {
+        let integral = Integral {
+            start_us: None,
+            last_us: 0,
+            current_na: 0,
+            charge: 0,
+        };
+        Self {
+            integrals: PerOutput::new(integral, integral),
+        }
+    }
+
+    /// Record the current of an output from the given timestamp on, sinking or sourcing.
+    pub fn record(&mut self, output: Output, timestamp_us: u64, current_ua: f32) {
+        let magnitude = if current_ua < 0.0 {
+            -current_ua
+        } else {
+            current_ua
+        };
+        let integral = &mut self.integrals[output];
+        integral.charge = integral.charge_at(timestamp_us);
+        integral.start_us.get_or_insert(timestamp_us);
+        integral.last_us = timestamp_us;
+        // `as u32` saturates and maps NaN to 0
+        integral.current_na = (magnitude as f64 * 1_000.0 + 0.5) as u32;
+    }
+
+    /// Get the charge delivered by an output until `now_us`, in microcoulombs.
+    pub fn charge_uc(&self, output: Output, now_us: u64) -> f32 {
+        (self.integrals[output].charge_at(now_us) as f64 / 1_000_000_000.0) as f32
+    }
+
+    /// Get the time-weighted average current of an output from its first record until
+    /// `now_us`, in microamps.
+    pub fn average_ua(&self, output: Output, now_us: u64) -> Option<f32> {
+        let integral = &self.integrals[output];
+        let elapsed = now_us.saturating_sub(integral.start_us?);
+        if elapsed == 0 {
+            return None;
+        }
+        Some((integral.charge_at(now_us) as f64 / elapsed as f64 / 1_000.0) as f32)
+    }
+
+    /// Forget the charge of an output.
+    pub fn reset(&mut self, output: Output) {
+        self.integrals[output] = Integral::default();
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432",
+        idents(AsyncI2c(sync = "I2c"), AsyncErrorType(sync = "ErrorType"))
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T> AsyncDS4432<I, T> {
+    /// Give the driver a charge meter fed by every write, or remove it
+    ///
+    /// Writes are only metered on the outputs with a known Rfs, once a clock is given with
+    /// `set_clock`.
+    pub fn set_meter(&mut self, meter: Option<ChargeMeter>) {
+        self.config.meter = meter;
+    }
+
+    /// Record the current of a register value written to an output in the meter
+    pub(crate) fn record_charge(&mut self, output: Output, value: u8) {
+        let Ok(scale) = self.scale(output) else {
+            return;
+        };
+        if let (Some(meter), Some(InstalledClock(clock))) =
+            (self.config.meter.as_mut(), self.config.clock)
+        {
+            meter.record(output, clock.now_us(), scale.current_ua(value & 0x7F));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+
+    #[test]
+    fn integrates_current_magnitude() {
+        let mut meter = ChargeMeter::new();
+        assert_eq!(meter.average_ua(Output::One, 10), None);
+
+        meter.record(Output::One, 1_000, 100.0);
+        meter.record(Output::One, 2_000, -50.0);
+        assert_eq!(meter.charge_uc(Output::One, 4_000), 0.2);
+        assert_eq!(meter.average_ua(Output::One, 3_000), Some(75.0));
+        assert_eq!(meter.charge_uc(Output::Zero, 4_000), 0.0);
+
+        meter.reset(Output::One);
+        assert_eq!(meter.charge_uc(Output::One, 4_000), 0.0);
+    }
+
+    #[cfg(all(feature = "sync", not(feature = "tiny")))]
+    #[test]
+    fn meters_every_write() {
+        use core::sync::atomic::{AtomicU64, Ordering};
+        use embedded_hal_mock::eh1::i2c;
+        use std::vec;
+
+        use crate::time::Clock;
+        use crate::{Status, DS4432, SLAVE_ADDRESS};
+
+        static NOW_US: AtomicU64 = AtomicU64::new(0);
+
+        struct TestClock;
+
+        impl Clock for TestClock {
+            fn now_us(&self) -> u64 {
+                NOW_US.load(Ordering::Relaxed)
+            }
+        }
+
+        let expectations = [
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x7F]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0x7F]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x00]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x7F]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::with_rfs(mock, Some(80_000), None).unwrap();
+        ds4432.set_clock(Some(&TestClock));
+        ds4432.set_meter(Some(ChargeMeter::new()));
+
+        ds4432.set_status(Output::Zero, Status::Sink(127)).unwrap();
+        NOW_US.store(1_000, Ordering::Relaxed);
+        ds4432.disable(Output::Zero).unwrap();
+        NOW_US.store(2_000, Ordering::Relaxed);
+        ds4432.enable(Output::Zero).unwrap();
+
+        // 98.921µA sunk for 2ms out of 3ms
+        let meter = ds4432.config().meter().unwrap();
+        assert_eq!(meter.charge_uc(Output::Zero, 3_000), 0.197842);
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(feature = "charge")]
+use crate::charge::ChargeMeter;
+use crate::derating::DeratingCurve;
+use crate::event::Verbosity;
+use crate::time::{Clock, InstalledClock};
+use crate::transfer::{Installed, TransferFn};
+use crate::{Output, PerOutput, Status};
+
+/// The configuration of a driver, kept aside while the I2C bus is reclaimed.
+///
+/// A configuration is taken out of a driver with `into_parts` and given back to a new driver
+/// with `from_parts`, so the bus can be used for something else in between without losing the
+/// Rfs values, labels or the statuses remembered by `disable`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Config {
+    pub(crate) rfs_ohm: PerOutput<Option<u32>>,
+    pub(crate) labels: PerOutput<Option<&'static str>>,
+    pub(crate) preserve_zero_polarity: bool,
+    pub(crate) setpoints: PerOutput<Option<[u8; 2]>>,
+    pub(crate) derating: Option<DeratingCurve>,
+    pub(crate) derated_code: Option<u8>,
+    pub(crate) verbosity: PerOutput<Verbosity>,
+    pub(crate) transfers: PerOutput<Option<Installed>>,
+    pub(crate) suspended: PerOutput<Option<u8>>,
+    pub(crate) clock: Option<InstalledClock>,
+    #[cfg(feature = "charge")]
+    pub(crate) meter: Option<ChargeMeter>,
+}
+
+impl Config {
+    /// Get the Rfs value of an output, if known.
+    pub fn rfs_ohm(&self, output: Output) -> Option<u32> {
+        self.rfs_ohm[output]
+    }
+
+    /// Get the name attached to an output, if any.
+    pub fn label(&self, output: Output) -> Option<&'static str> {
+        self.labels[output]
+    }
+
+    /// Whether `Source(0)` is written as 0x80 to keep the source direction.
+    pub fn preserve_zero_polarity(&self) -> bool {
+        self.preserve_zero_polarity
+    }
+
+    /// Get the two statuses `toggle` flips an output between, as raw DAC codes, if configured.
+    pub fn setpoints(&self, output: Output) -> Option<(Status, Status)> {
+        self.setpoints[output].map(|[first, second]| (first.into(), second.into()))
+    }
+
+    /// Get the derating curve clamping the codes, if configured.
+    pub fn derating(&self) -> Option<DeratingCurve> {
+        self.derating
+    }
+
+    /// Get the highest code allowed by the derating curve at the last reported temperature.
+    pub fn max_code(&self) -> u8 {
+        self.derated_code.unwrap_or(127)
+    }
+
+    /// Get what is logged about an output.
+    pub fn verbosity(&self, output: Output) -> Verbosity {
+        self.verbosity[output]
+    }
+
+    /// Get the transfer function installed on an output, if any.
+    pub fn transfer_fn(&self, output: Output) -> Option<&'static (dyn TransferFn + Sync)> {
+        self.transfers[output].map(|Installed(transfer)| transfer)
+    }
+
+    /// Get the status `enable` restores on an output turned off by `disable`, as a raw DAC code.
+    pub fn suspended(&self, output: Output) -> Option<Status> {
+        self.suspended[output].map(Status::from)
+    }
+
+    /// Get the clock timestamping the records of the driver, if any.
+    pub fn clock(&self) -> Option<&'static (dyn Clock + Sync)> {
+        self.clock.map(|InstalledClock(clock)| clock)
+    }
+
+    /// Get the charge meter fed by every write, if metering.
+    #[cfg(feature = "charge")]
+    pub fn meter(&self) -> Option<&ChargeMeter> {
+        self.meter.as_ref()
+    }
+}
@@ -0,0 +1,229 @@
+//! Live-tuning control channel.
+//!
+//! Lets the host debugger update the setpoints at runtime, to tune bias currents while watching
+//! the analog behavior without reflashing. The host sends [`script`](crate::script) lines
+//! (`set`, `wait`, `ramp`) over any byte stream, typically an RTT down channel, which are
+//! collected by a [`ControlChannel`] and applied by `poll_control`.
+//!
+//! With `rtt-target`, the firmware main loop would look like:
+//!
+//! ```ignore
+//! let channels = rtt_target::rtt_init! {
+//!     up: { 0: { size: 1024, name: "defmt" } }
+//!     down: { 0: { size: 64, name: "ds4432" } }
+//! };
+//! let mut down = channels.down.0;
+//! let mut control = ControlChannel::<64>::new();
+//!
+//! loop {
+//!     control.receive(|buf| down.read(buf));
+//!     ds4432.poll_control(&mut control, &mut delay)?;
+//!     // ...
+//! }
+//! ```
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c};
+//! use ds4432::control::ControlChannel;
+//! use ds4432::DS4432;
+//!
+//! # let i2c = i2c::Mock::new(&[
+//! #     i2c::Transaction::write(0x48, vec![0xF8, 0xAA]),
+//! # ]);
+//! let mut ds4432 = DS4432::new(i2c);
+//! let mut control = ControlChannel::<32>::new();
+//!
+//! control.receive(|buf| {
+//!     buf[..14].copy_from_slice(b"set out0 sourc");
+//!     14
+//! });
+//! assert_eq!(ds4432.poll_control(&mut control, &mut NoopDelay), Ok(0));
+//! control.receive(|buf| {
+//!     buf[..5].copy_from_slice(b"e 42\n");
+//!     5
+//! });
+//! assert_eq!(ds4432.poll_control(&mut control, &mut NoopDelay), Ok(1));
+//! # ds4432.release().done();
+//! ```
+
+#[cfg(feature = "async")]
+use crate::delay::AsyncDelaySource;
+#[cfg(feature = "sync")]
+use crate::delay::DelaySource;
+#[cfg(feature = "sync")]
+use crate::DS4432;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::ErrorType;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+use crate::script::{parse_step, ParseError, ScriptError};
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+
+/// A buffer of `N` bytes collecting the received control lines.
+///
+/// Lines longer than the buffer are dropped.
+#[derive(Debug, Clone)]
+pub struct ControlChannel<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+    /// Start of the bytes not handed out as lines yet
+    start: usize,
+    /// Whether the current line overflowed the buffer and is being dropped
+    dropping: bool,
+    /// Number of lines received, to report the line of parse errors
+    lines: usize,
+}
+
+impl<const N: usize> ControlChannel<N> {
+    /// Create an empty control channel.
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            len: 0,
+            start: 0,
+            dropping: false,
+            lines: 0,
+        }
+    }
+
+    /// Receive bytes from the transport, `read` filling the given free space and returning the
+    /// number of bytes written (e.g. `|buf| down_channel.read(buf)`).
+    pub fn receive(&mut self, read: impl FnOnce(&mut [u8]) -> usize) -> usize {
+        self.buf.copy_within(self.start..self.len, 0);
+        self.len -= self.start;
+        self.start = 0;
+
+        let count = read(&mut self.buf[self.len..]).min(N - self.len);
+        self.len += count;
+        if self.len == N && !self.buf.contains(&b'\n') {
+            warn!("control line longer than {} bytes dropped", N);
+            self.len = 0;
+            self.dropping = true;
+        }
+        count
+    }
+
+    /// Take the next complete line, if any.
+    ///
+    /// Lines which aren't valid UTF-8 are skipped.
+    pub fn next_line(&mut self) -> Option<&str> {
+        loop {
+            let end = self.buf[self.start..self.len]
+                .iter()
+                .position(|&b| b == b'\n')?;
+            let line = self.start..self.start + end;
+            self.start += end + 1;
+            // dropped lines are counted too, so errors report the line number of the stream
+            self.lines += 1;
+            if core::mem::take(&mut self.dropping) {
+                continue;
+            }
+            if core::str::from_utf8(&self.buf[line.clone()]).is_ok() {
+                return core::str::from_utf8(&self.buf[line]).ok();
+            }
+        }
+    }
+}
+
+impl<const N: usize> Default for ControlChannel<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432",
+        idents(
+            AsyncI2c(sync = "I2c"),
+            AsyncErrorType(sync = "ErrorType"),
+            AsyncDelaySource(sync = "DelaySource")
+        )
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T> AsyncDS4432<I, T> {
+    /// Apply the complete lines received on a control channel, returning the number of steps
+    /// executed
+    ///
+    /// A line which can't be parsed stops the polling with an error, the following lines are
+    /// kept for the next call.
+    pub async fn poll_control<const N: usize, D: AsyncDelaySource<T>>(
+        &mut self,
+        channel: &mut ControlChannel<N>,
+        delay: &mut D,
+    ) -> core::result::Result<usize, ScriptError<I::Error>> {
+        trace!("poll_control");
+
+        let mut count = 0;
+        while let Some(line) = channel.next_line() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let step = parse_step(line).map_err(|kind| ParseError {
+                line: channel.lines,
+                kind,
+            })?;
+            self.play_step(&step, delay).await?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal_mock::eh1::{delay::NoopDelay, i2c};
+    use std::vec;
+
+    use crate::script::ParseErrorKind;
+    use crate::{Output, SLAVE_ADDRESS};
+
+    fn send<const N: usize>(channel: &mut ControlChannel<N>, bytes: &[u8]) {
+        channel.receive(|buf| {
+            buf[..bytes.len()].copy_from_slice(bytes);
+            bytes.len()
+        });
+    }
+
+    #[test]
+    fn drops_long_lines_and_reports_bad_ones() {
+        let expectations = [i2c::Transaction::write(
+            SLAVE_ADDRESS,
+            vec![Output::One as u8, 0x05],
+        )];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::new(mock);
+        let mut channel = ControlChannel::<16>::new();
+
+        send(&mut channel, b"# a very long co");
+        send(&mut channel, b"mment\nset out9 ");
+        assert_eq!(ds4432.poll_control(&mut channel, &mut NoopDelay), Ok(0));
+        send(&mut channel, b"sink 5\n");
+        assert_eq!(
+            ds4432.poll_control(&mut channel, &mut NoopDelay),
+            Err(ScriptError::Parse(ParseError {
+                line: 2,
+                kind: ParseErrorKind::InvalidOutput
+            }))
+        );
+        send(&mut channel, b"set out1 sink 5\n");
+        assert_eq!(ds4432.poll_control(&mut channel, &mut NoopDelay), Ok(1));
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+}
@@ -0,0 +1,217 @@
+//! Driver-owned delay provider.
+//!
+//! The time-based methods (`ramp_to`, `pulse`, `toggle_repeat`, `play`, `run_script`,
+//! `log_csv_every`, `poll_control`, `handle_shell`, ...) take a delay source per call: either
+//! any `DelayNs`, or [`DriverDelay`] to use the delay provider the driver was given with
+//! `with_delay`. The driver keeps its delay across calls, even when the future of an async
+//! method is dropped before completion. The shorthands `wait`, `ramp`, `ramp_eased` and,
+//! with the `pulse` feature, `pulses` always use the driver's delay.
+//!
+//! Drivers built without a delay have the [`NoDelay`] placeholder type, which can't be used
+//! through [`DriverDelay`].
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c};
+//! use ds4432::delay::DriverDelay;
+//! use ds4432::{Output, Status, DS4432};
+//! use fugit::ExtU32;
+//!
+//! # let i2c = i2c::Mock::new(&[
+//! #     i2c::Transaction::write_read(0x48, vec![0xF8], vec![0x00]),
+//! #     i2c::Transaction::write(0x48, vec![0xF8, 0x81]),
+//! #     i2c::Transaction::write(0x48, vec![0xF8, 0x00]),
+//! #     i2c::Transaction::write(0x48, vec![0xF8, 0x81]),
+//! # ]);
+//! let mut ds4432 = DS4432::new(i2c).with_delay(NoopDelay);
+//!
+//! ds4432.ramp(Output::Zero, Status::Source(1), 1.millis()).unwrap();
+//! ds4432.wait(10.millis()).unwrap();
+//! ds4432
+//!     .set_setpoints(Output::Zero, Status::Disable, Status::Source(1))
+//!     .unwrap();
+//! ds4432
+//!     .toggle_repeat(Output::Zero, 2, 1.millis(), &mut DriverDelay)
+//!     .unwrap();
+//! # ds4432.release().done();
+//! ```
+
+#[cfg(feature = "sync")]
+use crate::DS4432;
+#[cfg(feature = "sync")]
+use embedded_hal::delay::DelayNs;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::ErrorType;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs as AsyncDelayNs;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+use fugit::MicrosDurationU32;
+
+use crate::easing::Easing;
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+use crate::{Output, Result, Status};
+
+/// The delay type of a driver built without a delay provider.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct NoDelay;
+
+/// The delay source of the time-based methods using the driver's own delay provider.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct DriverDelay;
+
+/// Where the blocking time-based methods get their delay provider from.
+#[cfg(feature = "sync")]
+pub trait DelaySource<T> {
+    /// The delay provider
+    type Delay: DelayNs;
+
+    /// Get the delay provider, given the one of the driver
+    fn provider<'a>(&'a mut self, driver: &'a mut T) -> &'a mut Self::Delay;
+}
+
+#[cfg(feature = "sync")]
+impl<T, D: DelayNs> DelaySource<T> for D {
+    type Delay = D;
+
+    fn provider<'a>(&'a mut self, _driver: &'a mut T) -> &'a mut D {
+        self
+    }
+}
+
+#[cfg(feature = "sync")]
+impl<T: DelayNs> DelaySource<T> for DriverDelay {
+    type Delay = T;
+
+    fn provider<'a>(&'a mut self, driver: &'a mut T) -> &'a mut T {
+        driver
+    }
+}
+
+/// Where the async time-based methods get their delay provider from.
+#[cfg(feature = "async")]
+pub trait AsyncDelaySource<T> {
+    /// The delay provider
+    type Delay: AsyncDelayNs;
+
+    /// Get the delay provider, given the one of the driver
+    fn provider<'a>(&'a mut self, driver: &'a mut T) -> &'a mut Self::Delay;
+}
+
+#[cfg(feature = "async")]
+impl<T, D: AsyncDelayNs> AsyncDelaySource<T> for D {
+    type Delay = D;
+
+    fn provider<'a>(&'a mut self, _driver: &'a mut T) -> &'a mut D {
+        self
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncDelayNs> AsyncDelaySource<T> for DriverDelay {
+    type Delay = T;
+
+    fn provider<'a>(&'a mut self, driver: &'a mut T) -> &'a mut T {
+        driver
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432",
+        idents(
+            AsyncI2c(sync = "I2c"),
+            AsyncErrorType(sync = "ErrorType"),
+            AsyncDelayNs(sync = "DelayNs")
+        )
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T: AsyncDelayNs> AsyncDS4432<I, T> {
+    /// Wait for the given duration, e.g. for an output to settle
+    pub async fn wait(&mut self, duration: MicrosDurationU32) -> Result<(), I::Error> {
+        trace!("wait");
+
+        self.delay.delay_us(duration.ticks()).await;
+        Ok(())
+    }
+
+    /// `ramp_to` using the driver's delay
+    pub async fn ramp(
+        &mut self,
+        output: Output,
+        target: Status,
+        duration: MicrosDurationU32,
+    ) -> Result<(), I::Error> {
+        trace!("ramp");
+
+        self.ramp_to(output, target, duration, &mut DriverDelay)
+            .await
+    }
+
+    /// `ramp_to_eased` using the driver's delay
+    pub async fn ramp_eased<E: Easing>(
+        &mut self,
+        output: Output,
+        target: Status,
+        duration: MicrosDurationU32,
+        easing: &E,
+    ) -> Result<(), I::Error> {
+        trace!("ramp_eased");
+
+        self.ramp_to_eased(output, target, duration, easing, &mut DriverDelay)
+            .await
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal_mock::eh1::i2c;
+    use fugit::ExtU32;
+    use std::vec;
+
+    use crate::time::{Clock, MockClock, MockDelay};
+    use crate::SLAVE_ADDRESS;
+
+    #[test]
+    fn uses_the_owned_delay() {
+        let expectations = [
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::One as u8], vec![0x00]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x81]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x01]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x02]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let clock = MockClock::new();
+        let mut ds4432 = DS4432::new(mock).with_delay(MockDelay::new(&clock));
+
+        ds4432
+            .ramp(Output::One, Status::Source(1), 50.micros())
+            .unwrap();
+        ds4432.wait(50.micros()).unwrap();
+        assert_eq!(clock.now_us(), 100);
+
+        ds4432
+            .set_setpoints(Output::Zero, Status::Sink(1), Status::Sink(2))
+            .unwrap();
+        ds4432
+            .toggle_repeat(Output::Zero, 2, 25.micros(), &mut DriverDelay)
+            .unwrap();
+        assert_eq!(clock.now_us(), 150);
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+}
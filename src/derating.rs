@@ -0,0 +1,167 @@
+//! Thermal derating.
+//!
+//! A [`DeratingCurve`] gives the highest code the board allows at a temperature, e.g. to keep the
+//! dissipation of the output stage within its limits. Once a curve is configured with
+//! `set_derating`, the application reports the ambient or junction temperature with
+//! `set_temperature` and the driver clamps every written code to the allowed one, reducing the
+//! outputs already above it.
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! # use embedded_hal_mock::eh1::i2c;
+//! use ds4432::derating::DeratingCurve;
+//! use ds4432::{Output, Status, DS4432};
+//!
+//! // full scale up to 50°C, down to code 40 at 85°C
+//! static CURVE: DeratingCurve = DeratingCurve::new(&[(50, 127), (85, 40)]);
+//!
+//! # let i2c = i2c::Mock::new(&[
+//! #     i2c::Transaction::write(0x48, vec![0xF8, 0xE4]),
+//! #     i2c::Transaction::write_read(0x48, vec![0xF8], vec![0xE4]),
+//! #     i2c::Transaction::write(0x48, vec![0xF8, 0xA8]),
+//! #     i2c::Transaction::write_read(0x48, vec![0xF9], vec![0x00]),
+//! #     i2c::Transaction::write_read(0x48, vec![0xF8], vec![0xA8]),
+//! #     i2c::Transaction::write(0x48, vec![0xF8, 0xA8]),
+//! #     i2c::Transaction::write_read(0x48, vec![0xF8], vec![0xA8]),
+//! # ]);
+//! let mut ds4432 = DS4432::new(i2c);
+//! ds4432.set_derating(Some(CURVE));
+//! ds4432.set_status(Output::Zero, Status::Source(100)).unwrap();
+//!
+//! ds4432.set_temperature(85.0).unwrap();
+//! assert_eq!(ds4432.status(Output::Zero).unwrap(), Status::Source(40));
+//! ds4432.set_status(Output::Zero, Status::Source(100)).unwrap();
+//! assert_eq!(ds4432.status(Output::Zero).unwrap(), Status::Source(40));
+//! # ds4432.release().done();
+//! ```
+
+#[cfg(feature = "sync")]
+use crate::DS4432;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::ErrorType;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+use crate::{Output, Result};
+
+/// The highest allowed code by temperature.
+///
+/// Made of `(celsius, max_code)` points sorted by temperature, linearly interpolated between
+/// them and flat outside of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct DeratingCurve {
+    points: &'static [(i16, u8)],
+}
+
+impl DeratingCurve {
+    /// Create a curve from `(celsius, max_code)` points sorted by temperature.
+    pub const fn new(points: &'static [(i16, u8)]) -> Self {
+        Self { points }
+    }
+
+    /// Get the highest allowed code at the given temperature, rounded down.
+    ///
+    /// An unknown (NaN) temperature gets the lowest code of the curve.
+    pub fn max_code(&self, celsius: f32) -> u8 {
+        let lowest = self.points.iter().map(|(_, code)| *code).min();
+        if celsius.is_nan() {
+            return lowest.unwrap_or(127);
+        }
+        let mut code = self.points.first().map_or(127, |(_, code)| *code);
+        for pair in self.points.windows(2) {
+            let ((t0, c0), (t1, c1)) = (pair[0], pair[1]);
+            if celsius <= t0 as f32 {
+                break;
+            }
+            code = if celsius >= t1 as f32 {
+                c1
+            } else {
+                let t = (celsius - t0 as f32) / (t1 - t0) as f32;
+                (c0 as f32 + (c1 as f32 - c0 as f32) * t) as u8
+            };
+        }
+        code.min(127)
+    }
+}
+
+/// Clamp the code of a register value, keeping its direction.
+pub(crate) fn clamp_register(value: u8, max_code: u8) -> u8 {
+    if value & 0x7F > max_code {
+        (value & 0x80) | max_code
+    } else {
+        value
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432",
+        idents(AsyncI2c(sync = "I2c"), AsyncErrorType(sync = "ErrorType"))
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T> AsyncDS4432<I, T> {
+    /// Configure the derating curve, or remove it
+    ///
+    /// The codes are only clamped from the next `set_temperature` on.
+    pub fn set_derating(&mut self, curve: Option<DeratingCurve>) {
+        self.config.derating = curve;
+        if curve.is_none() {
+            self.config.derated_code = None;
+        }
+    }
+
+    /// Report the board temperature, clamping the outputs above the code allowed by the
+    /// derating curve
+    pub async fn set_temperature(&mut self, celsius: f32) -> Result<(), I::Error> {
+        trace!("set_temperature");
+
+        let curve = match self.config.derating {
+            Some(curve) => curve,
+            None => return Ok(()),
+        };
+        let max_code = curve.max_code(celsius);
+        self.config.derated_code = Some(max_code);
+        for output in Output::ALL {
+            let value = self.read_register(output).await?;
+            if clamp_register(value, max_code) != value {
+                if self.config.verbosity[output].warns() {
+                    warn!("{:?} derated to code {}", output, max_code);
+                }
+                self.write_register(output, value).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the highest code currently allowed by the derating curve
+    pub fn max_code(&self) -> u8 {
+        self.config.max_code()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn curve_is_interpolated_and_flat_outside() {
+        let curve = DeratingCurve::new(&[(25, 127), (75, 27), (100, 0)]);
+        assert_eq!(curve.max_code(-40.0), 127);
+        assert_eq!(curve.max_code(25.0), 127);
+        assert_eq!(curve.max_code(50.0), 77);
+        assert_eq!(curve.max_code(87.5), 13);
+        assert_eq!(curve.max_code(150.0), 0);
+        assert_eq!(curve.max_code(f32::NAN), 0);
+        assert_eq!(DeratingCurve::new(&[]).max_code(25.0), 127);
+    }
+}
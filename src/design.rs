@@ -0,0 +1,64 @@
+//! Rfs selection.
+//!
+//! The full-scale current of an output (code 127) is set by its external Rfs resistor. These
+//! helpers give the resistor for a wanted full-scale current and check it against the datasheet
+//! recommended range, 40kΩ to 160kΩ (about 198µA down to 49µA).
+//!
+//! # Example
+//! ```
+//! use ds4432::design::{full_scale_ua, is_recommended_rfs, rfs_for_full_scale_ua};
+//!
+//! let rfs = rfs_for_full_scale_ua(100.0);
+//! assert_eq!(rfs, 79_137);
+//! assert!(is_recommended_rfs(rfs));
+//!
+//! // with the closest E96 value
+//! assert_eq!(full_scale_ua(78_700), 100.555115);
+//!
+//! // 200µA would need less than the recommended minimum
+//! assert!(!is_recommended_rfs(rfs_for_full_scale_ua(200.0)));
+//! ```
+
+use crate::{RECOMMENDED_RFS_MAX, RECOMMENDED_RFS_MIN};
+
+/// Full-scale current in microamps times Rfs in ohms, 127 codes of 62312.5µA·Ω.
+const FULL_SCALE_UA_OHM: f64 = 127.0 * 62_312.5;
+
+/// Get the Rfs value, rounded to the nearest ohm, giving the wanted full-scale current in
+/// microamps.
+///
+/// Saturates at `u32::MAX` for a zero or negative current.
+pub fn rfs_for_full_scale_ua(target_fs_ua: f32) -> u32 {
+    if target_fs_ua <= 0.0 {
+        return u32::MAX;
+    }
+    // `as u32` saturates
+    (FULL_SCALE_UA_OHM / target_fs_ua as f64 + 0.5) as u32
+}
+
+/// Get the full-scale current in microamps given by a Rfs value.
+pub fn full_scale_ua(rfs_ohm: u32) -> f32 {
+    (FULL_SCALE_UA_OHM / rfs_ohm as f64) as f32
+}
+
+/// Whether a Rfs value is in the datasheet recommended range.
+///
+/// Without the `not-recommended-rfs` feature, the driver only accepts these values.
+pub fn is_recommended_rfs(rfs_ohm: u32) -> bool {
+    (RECOMMENDED_RFS_MIN..=RECOMMENDED_RFS_MAX).contains(&rfs_ohm)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn recommended_range_bounds_full_scale() {
+        assert_eq!(rfs_for_full_scale_ua(full_scale_ua(40_000)), 40_000);
+        assert_eq!(rfs_for_full_scale_ua(full_scale_ua(160_000)), 160_000);
+        assert!(is_recommended_rfs(rfs_for_full_scale_ua(50.0)));
+        assert!(!is_recommended_rfs(rfs_for_full_scale_ua(45.0)));
+        assert_eq!(rfs_for_full_scale_ua(0.0), u32::MAX);
+        assert_eq!(rfs_for_full_scale_ua(-100.0), u32::MAX);
+    }
+}
@@ -0,0 +1,237 @@
+//! Easing profiles for ramps.
+//!
+//! A linear code ramp still produces a visible step response in some analog loops. An
+//! [`Easing`] shapes the progress of a ramp over time: `ramp_to_eased` updates the output at
+//! regular intervals with the code at the eased progress. Closures `Fn(f32) -> f32` can be used
+//! as custom profiles.
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c};
+//! use ds4432::easing::{Easing, SCurve};
+//! use ds4432::{Output, Status, DS4432};
+//! use fugit::ExtU32;
+//!
+//! assert_eq!(SCurve.ease(0.5), 0.5);
+//!
+//! # let i2c = i2c::Mock::new(&[
+//! #     i2c::Transaction::write_read(0x48, vec![0xF8], vec![0x00]),
+//! #     i2c::Transaction::write(0x48, vec![0xF8, 0x81]),
+//! #     i2c::Transaction::write(0x48, vec![0xF8, 0x82]),
+//! # ]);
+//! let mut ds4432 = DS4432::new(i2c);
+//! ds4432
+//!     .ramp_to_eased(Output::Zero, Status::Source(2), 10.millis(), &SCurve, &mut NoopDelay)
+//!     .unwrap();
+//! # ds4432.release().done();
+//! ```
+
+#[cfg(feature = "async")]
+use crate::delay::AsyncDelaySource;
+#[cfg(feature = "sync")]
+use crate::delay::DelaySource;
+#[cfg(feature = "sync")]
+use crate::DS4432;
+#[cfg(feature = "sync")]
+use embedded_hal::delay::DelayNs;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::ErrorType;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs as AsyncDelayNs;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+use fugit::MicrosDurationU32;
+
+use crate::derating::clamp_register;
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+use crate::{register_to_signed, Output, Result, Status};
+
+/// A ramp profile, mapping the elapsed fraction of a ramp to its progress.
+pub trait Easing {
+    /// Get the progress at the elapsed fraction `t`, both going from 0 to 1.
+    fn ease(&self, t: f32) -> f32;
+}
+
+impl<F: Fn(f32) -> f32> Easing for F {
+    fn ease(&self, t: f32) -> f32 {
+        self(t)
+    }
+}
+
+/// Constant speed.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Linear;
+
+impl Easing for Linear {
+    fn ease(&self, t: f32) -> f32 {
+        t
+    }
+}
+
+/// Fast start slowing down toward the target, like a first-order low-pass filter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct ExponentialApproach {
+    /// The number of time constants over the ramp duration, the higher the sharper
+    pub rate: f32,
+}
+
+impl Default for ExponentialApproach {
+    fn default() -> Self {
+        Self { rate: 5.0 }
+    }
+}
+
+impl Easing for ExponentialApproach {
+    fn ease(&self, t: f32) -> f32 {
+        if self.rate <= 0.0 {
+            return t;
+        }
+        // normalized so the target is reached at the end of the ramp
+        (1.0 - exp(-self.rate * t)) / (1.0 - exp(-self.rate))
+    }
+}
+
+/// Slow start and slow end (smoothstep).
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct SCurve;
+
+impl Easing for SCurve {
+    fn ease(&self, t: f32) -> f32 {
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+/// Compute e^x, within a few ulps for the non-positive values used by the easing profiles.
+fn exp(x: f32) -> f32 {
+    if x < -87.0 {
+        return 0.0;
+    }
+    // e^x = 2^n * e^f with f in [0, ln 2)
+    let y = x * core::f32::consts::LOG2_E;
+    let n = y as i32 - (y < (y as i32) as f32) as i32;
+    let f = (y - n as f32) * core::f32::consts::LN_2;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for k in 1..=8 {
+        term *= f / k as f32;
+        sum += term;
+    }
+    sum * f32::from_bits(((n + 127) as u32) << 23)
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432",
+        idents(
+            AsyncI2c(sync = "I2c"),
+            AsyncErrorType(sync = "ErrorType"),
+            AsyncDelaySource(sync = "DelaySource")
+        )
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T> AsyncDS4432<I, T> {
+    /// Move an output from its current status to the target one over the given duration,
+    /// following an easing profile
+    ///
+    /// The output is updated at regular intervals, once per code of the ramp, and only written
+    /// when its code changes.
+    pub async fn ramp_to_eased<E: Easing, D: AsyncDelaySource<T>>(
+        &mut self,
+        output: Output,
+        target: Status,
+        duration: MicrosDurationU32,
+        easing: &E,
+        delay: &mut D,
+    ) -> Result<(), I::Error> {
+        trace!("ramp_to_eased");
+
+        let end = clamp_register(self.encode(output, target)?, self.config.max_code());
+        let start = self.read_register(output).await?;
+        let steps = (register_to_signed(end) - register_to_signed(start)).unsigned_abs() as u32;
+        if steps == 0 {
+            return Ok(());
+        }
+
+        let (from, to) = (Status::from(start), Status::from(end));
+        let mut last = start;
+        let mut elapsed_us = 0;
+        for step in 1..=steps {
+            let t = if step == steps {
+                1.0
+            } else {
+                easing.ease(step as f32 / steps as f32)
+            };
+            let value = self.encode(output, Status::lerp(from, to, t))?;
+            if value != last {
+                self.write_register(output, value).await?;
+                last = value;
+            }
+            // spread the remainder of the division over the steps
+            let deadline_us = (duration.ticks() as u64 * step as u64 / steps as u64) as u32;
+            delay
+                .provider(&mut self.delay)
+                .delay_us(deadline_us - elapsed_us)
+                .await;
+            elapsed_us = deadline_us;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal_mock::eh1::i2c;
+    use fugit::ExtU32;
+    use std::vec;
+
+    use crate::time::{Clock, MockClock, MockDelay};
+    use crate::SLAVE_ADDRESS;
+
+    #[test]
+    fn exp_is_accurate() {
+        for (x, expected) in [(0.0, 1.0), (-1.0, 0.367_879_4), (-5.0, 0.006_737_947)] {
+            assert!((exp(x) - expected).abs() <= expected * 1e-6);
+        }
+    }
+
+    #[test]
+    fn approaches_target_exponentially() {
+        let expectations = [
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::One as u8], vec![0x00]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x02]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x03]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x04]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::new(mock);
+        let clock = MockClock::new();
+
+        ds4432
+            .ramp_to_eased(
+                Output::One,
+                Status::Sink(4),
+                102.micros(),
+                &ExponentialApproach { rate: 3.0 },
+                &mut MockDelay::new(&clock),
+            )
+            .unwrap();
+        assert_eq!(clock.now_us(), 102);
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+}
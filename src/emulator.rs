@@ -0,0 +1,114 @@
+//! DS4432 target-mode emulator.
+//!
+//! [`Emulator`] implements the device side of the DS4432 protocol, so firmware on a MCU with an
+//! I2C target (slave) peripheral can pretend to be the chip and host-side software or other
+//! masters can be integration-tested without real silicon. It is the register model behind the
+//! `MockBus` of the `mock` feature.
+//!
+//! The emulator only handles the bytes of a transfer, the peripheral answering the DS4432
+//! address (0x48) and calling:
+//! - [`Emulator::on_write`] with the bytes written by the master, NACKing them if it returns
+//!   `false`.
+//! - [`Emulator::on_read`] with the buffer to send back to the master, e.g. after the register
+//!   address of a write-read.
+//!
+//! # Example
+//! ```
+//! use ds4432::emulator::Emulator;
+//! use ds4432::Output;
+//!
+//! let mut device = Emulator::new();
+//! // a master sets OUT1 to source code 42, then reads it back
+//! assert!(device.on_write(&[0xF9, 0xAA]));
+//! assert!(device.on_write(&[0xF9]));
+//! let mut buf = [0x00];
+//! device.on_read(&mut buf);
+//! assert_eq!(buf, [0xAA]);
+//! assert_eq!(device.register(Output::One), 0xAA);
+//! ```
+
+use crate::{Output, PerOutput};
+
+/// The registers of a DS4432, as seen from the bus.
+#[derive(Debug, Clone)]
+pub struct Emulator {
+    registers: PerOutput<u8>,
+    pointer: u8,
+}
+
+impl Emulator {
+    /// Create a device in its power-on state, both outputs disabled.
+    pub const fn new() -> Self {
+        Self {
+            registers: PerOutput::new(0x00, 0x00),
+            pointer: Output::Zero as u8,
+        }
+    }
+
+    /// Get the register value of an output.
+    pub fn register(&self, output: Output) -> u8 {
+        self.registers[output]
+    }
+
+    /// Change the register value of an output, e.g. from a local control.
+    pub fn set_register(&mut self, output: Output, value: u8) {
+        self.registers[output] = value;
+    }
+
+    /// Handle the bytes written by a master, returning whether they are acknowledged.
+    ///
+    /// The first byte selects the register, the following ones are written into it, the last
+    /// one being kept. An unknown register address is not acknowledged.
+    pub fn on_write(&mut self, bytes: &[u8]) -> bool {
+        let (pointer, values) = match bytes.split_first() {
+            Some(split) => split,
+            None => return true,
+        };
+        let output = match Self::output(*pointer) {
+            Some(output) => output,
+            None => return false,
+        };
+        self.pointer = *pointer;
+        if let Some(value) = values.last() {
+            self.registers[output] = *value;
+        }
+        true
+    }
+
+    /// Fill the bytes read by a master with the value of the selected register.
+    pub fn on_read(&self, buf: &mut [u8]) {
+        let value = Self::output(self.pointer).map_or(0xFF, |output| self.registers[output]);
+        buf.fill(value);
+    }
+
+    /// The output of a register address.
+    fn output(pointer: u8) -> Option<Output> {
+        Output::ALL
+            .into_iter()
+            .find(|output| *output as u8 == pointer)
+    }
+}
+
+impl Default for Emulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nacks_unknown_registers() {
+        let mut device = Emulator::new();
+        assert!(device.on_write(&[0xF8, 0x01, 0x85]));
+        assert!(device.on_write(&[]));
+        assert!(!device.on_write(&[0xF7, 0x2A]));
+
+        let mut buf = [0x00; 2];
+        device.on_read(&mut buf);
+        assert_eq!(buf, [0x85, 0x85]);
+        assert_eq!(device.register(Output::One), 0x00);
+    }
+}
@@ -1,28 +1,90 @@
+use crate::Output;
+
 /// Driver Result type.
 pub type Result<T, E> = core::result::Result<T, Error<E>>;
 
 /// Driver errors.
+///
+/// New variants may be added in the future, so matching on this enum requires a wildcard arm.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum Error<E> {
     /// I2C bus error.
     I2c(E),
     /// The given code is too high
-    InvalidCode(u8),
-    /// The given Iout is out of range
+    InvalidCode { code: u8, max: u8 },
+    /// The given Iout is negative or above the full scale of the output
     InvalidIout,
     /// The given RFS is out of range
-    InvalidRfs,
-    /// Try to set a Current value without giving the Rfs value
-    UnknownRfs,
+    InvalidRfs { rfs_ohm: u32 },
+    /// Try to set a Current value without giving the Rfs value of the output
+    UnknownRfs { output: Output },
+    /// Try to toggle an output without configuring its setpoints
+    UnknownSetpoints { output: Output },
+    /// Try to convert a Current value into a register value without a Rfs value
+    CurrentWithoutRfs,
+    /// Try to drive an output in a quantity without installing its transfer function
+    UnknownTransferFn { output: Output },
+    /// Two redundant devices hold different register values for the same output
+    Divergence {
+        output: Output,
+        primary: u8,
+        secondary: u8,
+    },
+    /// The register value read back doesn't match the one written
+    Verify {
+        output: Output,
+        expected: u8,
+        actual: u8,
+    },
+    /// Repeated readings of an output don't agree on a majority value
+    Inconsistent { output: Output },
+    /// Try to take a majority out of no readings
+    NoSamples,
 }
 
-#[cfg(feature = "core-error")]
-impl<E: core::fmt::Debug> core::error::Error for Error<E> {}
+// `std::error::Error` is a re-export of `core::error::Error`, only reachable through `core` from
+// Rust 1.81.0
+#[cfg(all(feature = "core-error", not(feature = "std")))]
+use core::error::Error as StdError;
+#[cfg(feature = "std")]
+use std::error::Error as StdError;
 
-#[cfg(feature = "core-error")]
+#[cfg(any(feature = "core-error", feature = "std"))]
+impl<E: StdError + 'static> StdError for Error<E> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::I2c(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(any(feature = "core-error", feature = "std"))]
 impl<E: core::fmt::Debug> core::fmt::Display for Error<E> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{self:?}")
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use std::io;
+    use std::string::ToString;
+
+    #[test]
+    fn i2c_error_is_the_source() {
+        let error: Error<io::Error> = Error::I2c(io::Error::new(io::ErrorKind::Other, "nack"));
+        assert_eq!(error.source().unwrap().to_string(), "nack");
+        assert!(Error::<io::Error>::InvalidIout.source().is_none());
+    }
+
+    #[cfg(feature = "core-error")]
+    #[test]
+    fn std_keeps_the_core_error_impl() {
+        fn assert_core_error<E: core::error::Error>() {}
+        assert_core_error::<Error<io::Error>>();
+    }
+}
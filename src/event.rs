@@ -0,0 +1,161 @@
+//! Structured driver events.
+//!
+//! Every register access is logged as an [`Event`] so host-side tooling can filter and plot the
+//! driver activity. With `defmt-03` the event is encoded field by field, with `log` it is
+//! formatted as `key=value` pairs:
+//!
+//! ```text
+//! access=write channel=0 label="VCORE trim" code=42 polarity=source current_ua=32.714 result=ok
+//! ```
+//!
+//! On top of the compile-time `log`/`defmt` filters, the events and warnings of each output
+//! (derating, disagreeing readings, ...) are filtered at runtime by its [`Verbosity`], set with
+//! `set_verbosity`, e.g. to follow a tuning loop on one output while the other stays quiet. The
+//! method traces aren't tied to an output and are only filtered at compile time.
+
+use core::fmt;
+
+use crate::{Output, Scale};
+
+/// The kind of register access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// The current direction of an output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum Polarity {
+    Sink,
+    Source,
+}
+
+/// What is logged about an output.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum Verbosity {
+    /// Nothing is logged
+    Off,
+    /// Only the warnings and the failed accesses are logged
+    Error,
+    /// The warnings and every access are logged, the successful accesses at debug level
+    #[default]
+    Debug,
+}
+
+impl Verbosity {
+    /// Whether an event is logged at this verbosity.
+    pub fn logs(self, event: &Event) -> bool {
+        match self {
+            Verbosity::Off => false,
+            Verbosity::Error => !event.ok,
+            Verbosity::Debug => true,
+        }
+    }
+
+    /// Whether the warnings about an output are logged at this verbosity.
+    pub fn warns(self) -> bool {
+        self != Verbosity::Off
+    }
+}
+
+/// A register access of the driver.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Event {
+    /// Whether the register was read or written
+    pub access: Access,
+    /// The accessed output
+    pub output: Output,
+    /// The name attached to the output, if any
+    pub label: Option<&'static str>,
+    /// The DAC code, meaningless if a read failed
+    pub code: u8,
+    /// The current direction, meaningless if a read failed
+    pub polarity: Polarity,
+    /// The current in microamps, if the Rfs is known
+    pub current_ua: Option<f32>,
+    /// Whether the I2C transaction succeeded
+    pub ok: bool,
+}
+
+impl Event {
+    pub(crate) fn new(access: Access, output: Output, value: u8, scale: Option<Scale>) -> Self {
+        let code = value & 0x7F;
+        Self {
+            access,
+            output,
+            label: None,
+            code,
+            polarity: if value & 0x80 == 0x80 {
+                Polarity::Source
+            } else {
+                Polarity::Sink
+            },
+            current_ua: scale.map(|scale| scale.current_ua(code)),
+            ok: true,
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let access = match self.access {
+            Access::Read => "read",
+            Access::Write => "write",
+        };
+        write!(f, "access={} channel={}", access, self.output.index())?;
+        if let Some(label) = self.label {
+            write!(f, " label={:?}", label)?;
+        }
+        if self.ok || self.access == Access::Write {
+            let polarity = match self.polarity {
+                Polarity::Sink => "sink",
+                Polarity::Source => "source",
+            };
+            write!(f, " code={} polarity={}", self.code, polarity)?;
+            #[cfg(not(feature = "tiny"))]
+            if let Some(current) = self.current_ua {
+                write!(f, " current_ua={:.3}", current)?;
+            }
+        }
+        write!(f, " result={}", if self.ok { "ok" } else { "err" })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn formats_as_key_value_pairs() {
+        let mut event = Event::new(Access::Write, Output::One, 0xAA, Some(Scale::new(80_000)));
+        event.label = Some("VCORE trim");
+        #[cfg(not(feature = "tiny"))]
+        assert_eq!(
+            format!("{}", event),
+            "access=write channel=1 label=\"VCORE trim\" code=42 polarity=source current_ua=32.714 result=ok"
+        );
+        #[cfg(feature = "tiny")]
+        assert_eq!(
+            format!("{}", event),
+            "access=write channel=1 label=\"VCORE trim\" code=42 polarity=source result=ok"
+        );
+
+        let mut event = Event::new(Access::Read, Output::Zero, 0x00, None);
+        event.ok = false;
+        assert_eq!(format!("{}", event), "access=read channel=0 result=err");
+        assert!(Verbosity::Error.logs(&event));
+        assert!(!Verbosity::Off.logs(&event));
+        event.ok = true;
+        assert!(!Verbosity::Error.logs(&event));
+        assert!(Verbosity::default().logs(&event));
+        assert!(Verbosity::Error.warns() && !Verbosity::Off.warns());
+    }
+}
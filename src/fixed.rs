@@ -0,0 +1,252 @@
+//! DS4432 driver for boards with fixed Rfs resistors.
+//!
+//! When the Rfs values are known at build time, they can be given as const generics: the
+//! values are checked at compile time, so the microamps statuses are always available without
+//! any [`Error::UnknownRfs`](crate::Error::UnknownRfs) case. The other methods of the driver
+//! are reached through `Deref`.
+//!
+//! With the `tiny` feature, only the raw code statuses of the inner driver are left.
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! # use embedded_hal_mock::eh1::i2c;
+//! use ds4432::fixed::DS4432Fixed;
+//! use ds4432::{Output, Status};
+//!
+//! # #[cfg(not(feature = "tiny"))] {
+//! # let i2c = i2c::Mock::new(&[
+//! #     i2c::Transaction::write(0x48, vec![0xF9, 0x70]),
+//! #     i2c::Transaction::write_read(0x48, vec![0xF9], vec![0x70]),
+//! #     i2c::Transaction::write(0x48, vec![0xF9, 0x00]),
+//! # ]);
+//! let mut ds4432 = DS4432Fixed::<_, 80_000, 80_000>::new(i2c);
+//! ds4432.set_status(Output::One, Status::SinkMicroAmp(88.0)).unwrap();
+//! ds4432.disable(Output::One).unwrap();
+//! # ds4432.release().done();
+//! # }
+//! ```
+
+#[cfg(feature = "sync")]
+use crate::DS4432;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::ErrorType;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+use core::ops::{Deref, DerefMut};
+
+#[cfg(not(feature = "tiny"))]
+use crate::derating::clamp_register;
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+#[cfg(not(feature = "tiny"))]
+use crate::{Output, Result, Scale, Status};
+#[cfg(not(feature = "not-recommended-rfs"))]
+use crate::{RECOMMENDED_RFS_MAX, RECOMMENDED_RFS_MIN};
+
+/// A DS4432 Digital To Analog (DAC) converter on the I2C bus `I`, with the Rfs values
+/// `RFS0_OHM` and `RFS1_OHM`.
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432Fixed",
+        idents(AsyncDS4432(sync = "DS4432"))
+    ),
+    async(feature = "async", keep_self)
+)]
+pub struct AsyncDS4432Fixed<I, const RFS0_OHM: u32, const RFS1_OHM: u32> {
+    inner: AsyncDS4432<I>,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432Fixed",
+        idents(
+            AsyncDS4432(sync = "DS4432"),
+            AsyncI2c(sync = "I2c"),
+            AsyncErrorType(sync = "ErrorType")
+        )
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, const RFS0_OHM: u32, const RFS1_OHM: u32>
+    AsyncDS4432Fixed<I, RFS0_OHM, RFS1_OHM>
+{
+    #[cfg(not(feature = "not-recommended-rfs"))]
+    const VALID_RFS: () = ::core::assert!(
+        RFS0_OHM >= RECOMMENDED_RFS_MIN
+            && RFS0_OHM <= RECOMMENDED_RFS_MAX
+            && RFS1_OHM >= RECOMMENDED_RFS_MIN
+            && RFS1_OHM <= RECOMMENDED_RFS_MAX,
+        "Rfs values are out of the recommended range"
+    );
+    #[cfg(feature = "not-recommended-rfs")]
+    const VALID_RFS: () = ::core::assert!(RFS0_OHM != 0 && RFS1_OHM != 0, "Rfs values can't be 0");
+
+    /// Create a new DS4432 using the given I2C implementation.
+    ///
+    /// Invalid Rfs values are reported at compile time.
+    pub fn new(i2c: I) -> Self {
+        trace!("new");
+        let () = Self::VALID_RFS;
+        Self {
+            inner: AsyncDS4432::new(i2c),
+        }
+    }
+
+    /// Set the current sink/source status and code of an output, returning the status actually
+    /// applied, in microamps
+    #[cfg(not(feature = "tiny"))]
+    pub async fn set_status(&mut self, output: Output, status: Status) -> Result<Status, I::Error> {
+        trace!("set_status");
+
+        let status = match status {
+            Status::SinkMicroAmp(current) => Status::Sink(Self::scale(output).code(current)?),
+            Status::SourceMicroAmp(current) => Status::Source(Self::scale(output).code(current)?),
+            _ => status,
+        };
+        let value = clamp_register(self.inner.encode(output, status)?, self.inner.max_code());
+        self.inner.write_register(output, value).await?;
+        Ok(Self::decode(output, value))
+    }
+
+    /// Get the current sink/source status of an output, in microamps
+    #[cfg(not(feature = "tiny"))]
+    pub async fn status(&mut self, output: Output) -> Result<Status, I::Error> {
+        trace!("status");
+
+        let value = self.inner.read_register(output).await?;
+        Ok(Self::decode(output, value))
+    }
+
+    /// Convert the register value of an output into a Status in microamps
+    #[cfg(not(feature = "tiny"))]
+    fn decode(output: Output, value: u8) -> Status {
+        let scale = Self::scale(output);
+        match Status::from(value) {
+            Status::Sink(code) => Status::SinkMicroAmp(scale.current_ua(code)),
+            Status::Source(code) => Status::SourceMicroAmp(scale.current_ua(code)),
+            status => status,
+        }
+    }
+
+    /// Get the convertion factors of an output, folded into constants by the compiler
+    #[cfg(not(feature = "tiny"))]
+    fn scale(output: Output) -> Scale {
+        Scale::new(match output {
+            Output::Zero => RFS0_OHM,
+            Output::One => RFS1_OHM,
+        })
+    }
+
+    /// Return the underlying I2C device
+    pub fn release(self) -> I {
+        self.inner.release()
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432Fixed",
+        idents(AsyncDS4432(sync = "DS4432"))
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I, const RFS0_OHM: u32, const RFS1_OHM: u32> Deref
+    for AsyncDS4432Fixed<I, RFS0_OHM, RFS1_OHM>
+{
+    type Target = AsyncDS4432<I>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432Fixed",
+        idents(AsyncDS4432(sync = "DS4432"))
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I, const RFS0_OHM: u32, const RFS1_OHM: u32> DerefMut
+    for AsyncDS4432Fixed<I, RFS0_OHM, RFS1_OHM>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal_mock::eh1::i2c;
+    use std::vec;
+
+    #[cfg(not(feature = "tiny"))]
+    use crate::Error;
+    use crate::{Output, Status, SLAVE_ADDRESS};
+
+    #[cfg(not(feature = "tiny"))]
+    #[test]
+    fn can_get_output_0_status_current() {
+        let expectations = [i2c::Transaction::write_read(
+            SLAVE_ADDRESS,
+            vec![Output::Zero as u8],
+            vec![0xAA],
+        )];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432Fixed::<_, 80_000, 120_000>::new(mock);
+
+        let status = ds4432.status(Output::Zero).unwrap();
+        assert_eq!(status, Status::SourceMicroAmp(32.71406));
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[cfg(not(feature = "tiny"))]
+    #[test]
+    fn rejects_out_of_range_current() {
+        let mock = i2c::Mock::new(&[]);
+        let mut ds4432 = DS4432Fixed::<_, 80_000, 160_000>::new(mock);
+
+        assert_eq!(
+            ds4432.set_status(Output::One, Status::SourceMicroAmp(200.0)),
+            Err(Error::InvalidIout)
+        );
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[test]
+    fn reaches_the_inner_driver() {
+        let expectations = [
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x2A]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0x2A]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x00]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x2A]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432Fixed::<_, 80_000, 120_000>::new(mock);
+
+        ds4432.set_status(Output::Zero, Status::Sink(42)).unwrap();
+        ds4432.disable(Output::Zero).unwrap();
+        ds4432.enable(Output::Zero).unwrap();
+        assert!(ds4432.config().suspended(Output::Zero).is_none());
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+}
@@ -106,11 +106,11 @@ macro_rules! panic {
 macro_rules! trace {
     ($s:literal $(, $x:expr)* $(,)?) => {
         {
-            #[cfg(feature = "log")]
+            #[cfg(all(feature = "log", not(feature = "tiny")))]
             ::log::trace!($s $(, $x)*);
-            #[cfg(feature = "defmt-03")]
+            #[cfg(all(feature = "defmt-03", not(feature = "tiny")))]
             ::defmt::trace!($s $(, $x)*);
-            #[cfg(not(any(feature = "log", feature="defmt-03")))]
+            #[cfg(any(feature = "tiny", not(any(feature = "log", feature="defmt-03"))))]
             let _ = ($( & $x ),*);
         }
     };
@@ -119,11 +119,11 @@ macro_rules! trace {
 macro_rules! debug {
     ($s:literal $(, $x:expr)* $(,)?) => {
         {
-            #[cfg(feature = "log")]
+            #[cfg(all(feature = "log", not(feature = "tiny")))]
             ::log::debug!($s $(, $x)*);
-            #[cfg(feature = "defmt-03")]
+            #[cfg(all(feature = "defmt-03", not(feature = "tiny")))]
             ::defmt::debug!($s $(, $x)*);
-            #[cfg(not(any(feature = "log", feature="defmt-03")))]
+            #[cfg(any(feature = "tiny", not(any(feature = "log", feature="defmt-03"))))]
             let _ = ($( & $x ),*);
         }
     };
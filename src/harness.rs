@@ -0,0 +1,127 @@
+//! Behavioral tests shared by both driver flavours.
+//!
+//! `dual_test!` takes a scenario written once against the async driver on a
+//! [`MockBus`](crate::mock::MockBus), derives the blocking version with `maybe_async_cfg` the
+//! same way the driver is, and generates a `sync` and an `asynchronous` test out of them, for
+//! the enabled features. Any divergence between the generated drivers fails one of the two.
+
+#[cfg(feature = "async")]
+use core::future::Future;
+#[cfg(feature = "async")]
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+/// Run a future to completion by polling it in a loop.
+///
+/// Enough for the in-memory bus and the mock delay, which never have to wait.
+#[cfg(feature = "async")]
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    fn raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            raw_waker()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(core::ptr::null(), &VTABLE)
+    }
+
+    // SAFETY: the vtable functions ignore the data pointer and do nothing
+    let waker = unsafe { Waker::from_raw(raw_waker()) };
+    let mut context = Context::from_waker(&waker);
+    let mut future = core::pin::pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+/// Generate a test per driver flavour from an async scenario.
+///
+/// The scenario gets a driver on a fresh [`MockBus`](crate::mock::MockBus).
+macro_rules! dual_test {
+    ($name:ident, |$ds4432:ident| $body:block) => {
+        mod $name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            #[maybe_async_cfg::maybe(
+                sync(
+                    feature = "sync",
+                    idents(AsyncMockDs4432(sync = "MockDs4432"))
+                ),
+                async(feature = "async")
+            )]
+            async fn scenario($ds4432: &mut crate::mock::AsyncMockDs4432) $body
+
+            #[cfg(feature = "sync")]
+            #[test]
+            fn sync() {
+                scenario_sync(&mut crate::mock::MockDs4432::new(
+                    crate::mock::MockBus::new(),
+                ));
+            }
+
+            #[cfg(feature = "async")]
+            #[test]
+            fn asynchronous() {
+                crate::harness::block_on(scenario_async(&mut crate::mock::AsyncMockDs4432::new(
+                    crate::mock::MockBus::new(),
+                )));
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use fugit::ExtU32;
+
+    use crate::time::{Clock, MockClock, MockDelay};
+    use crate::{Output, Status};
+
+    dual_test!(status_round_trips, |ds4432| {
+        ds4432
+            .set_status(Output::Zero, Status::Sink(42))
+            .await
+            .unwrap();
+        ds4432
+            .set_status(Output::One, Status::Source(0))
+            .await
+            .unwrap();
+        assert_eq!(ds4432.status(Output::Zero).await.unwrap(), Status::Sink(42));
+        assert_eq!(ds4432.status(Output::One).await.unwrap(), Status::Disable);
+    });
+
+    dual_test!(enable_restores_disabled_output, |ds4432| {
+        ds4432
+            .set_status(Output::One, Status::Source(7))
+            .await
+            .unwrap();
+        ds4432.disable(Output::One).await.unwrap();
+        assert_eq!(ds4432.status(Output::One).await.unwrap(), Status::Disable);
+        ds4432.enable(Output::One).await.unwrap();
+        assert_eq!(ds4432.status(Output::One).await.unwrap(), Status::Source(7));
+    });
+
+    dual_test!(ramp_crosses_zero_in_time, |ds4432| {
+        let clock = MockClock::new();
+        ds4432
+            .set_status(Output::Zero, Status::Sink(2))
+            .await
+            .unwrap();
+        ds4432
+            .ramp_to(
+                Output::Zero,
+                Status::Source(2),
+                40.micros(),
+                &mut MockDelay::new(&clock),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            ds4432.status(Output::Zero).await.unwrap(),
+            Status::Source(2)
+        );
+        assert_eq!(clock.now_us(), 40);
+    });
+}
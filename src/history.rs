@@ -0,0 +1,266 @@
+//! Timestamped history of the outputs statuses.
+//!
+//! The history keeps the last `N` statuses of each output in a ring buffer so trends can be
+//! analysed (e.g. "what was the trim over the last minute"). Once given a history with
+//! `set_history` and a clock with `set_clock`, the driver records every status it writes to
+//! the outputs or reads back from them, keeping the last [`DRIVER_DEPTH`] of each output. A
+//! history can also be fed by the application, with a timestamp from its own [`Clock`].
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! # use embedded_hal_mock::eh1::i2c;
+//! use ds4432::history::{History, Origin};
+//! use ds4432::time::Clock;
+//! use ds4432::{Output, Status, DS4432};
+//!
+//! # struct Uptime;
+//! # impl Clock for Uptime {
+//! #     fn now_us(&self) -> u64 {
+//! #         1_000
+//! #     }
+//! # }
+//! # let i2c = i2c::Mock::new(&[
+//! #     i2c::Transaction::write(0x48, vec![0xF8, 0xAA]),
+//! #     i2c::Transaction::write_read(0x48, vec![0xF8], vec![0xAA]),
+//! # ]);
+//! let mut ds4432 = DS4432::new(i2c);
+//! ds4432.set_clock(Some(&Uptime));
+//! ds4432.set_history(Some(History::new()));
+//!
+//! ds4432.set_status(Output::Zero, Status::Source(42)).unwrap();
+//! ds4432.status(Output::Zero).unwrap();
+//!
+//! let history = ds4432.history().unwrap();
+//! assert_eq!(history.latest(Output::Zero).unwrap().origin, Origin::Readback);
+//! assert_eq!(history.since(Output::Zero, 1_000).count(), 2);
+//! assert_eq!(history.entries(Output::One).count(), 0);
+//! # ds4432.release().done();
+//! ```
+
+#[cfg(feature = "sync")]
+use crate::DS4432;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::ErrorType;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+use heapless::HistoryBuffer;
+
+use crate::time::{Clock, InstalledClock};
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+use crate::{Output, PerOutput, Status};
+
+/// Number of statuses of each output kept by the history of a driver.
+pub const DRIVER_DEPTH: usize = 16;
+
+/// Where a recorded Status comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum Origin {
+    /// The Status was written to the device
+    Commanded,
+    /// The Status was read from the device
+    Readback,
+}
+
+/// A timestamped Status.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Entry {
+    /// When the Status was recorded, in microseconds
+    pub timestamp_us: u64,
+    /// Where the Status comes from
+    pub origin: Origin,
+    /// The recorded Status
+    pub status: Status,
+}
+
+/// The last `N` statuses of each output.
+#[derive(Debug)]
+pub struct History<const N: usize> {
+    buffers: PerOutput<HistoryBuffer<Entry, N>>,
+}
+
+impl<const N: usize> Default for History<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> History<N> {
+    /// Create an empty history.
+    pub const fn new() -> Self {
+        Self {
+            buffers: PerOutput::new(HistoryBuffer::new(), HistoryBuffer::new()),
+        }
+    }
+
+    /// Record a Status of an output, overwriting the oldest entry if the history is full.
+    pub fn record(&mut self, output: Output, timestamp_us: u64, origin: Origin, status: Status) {
+        self.buffers[output].write(Entry {
+            timestamp_us,
+            origin,
+            status,
+        });
+    }
+
+    /// Record a Status of an output, timestamped with the current time of the given clock.
+    pub fn record_now(
+        &mut self,
+        output: Output,
+        clock: &impl Clock,
+        origin: Origin,
+        status: Status,
+    ) {
+        self.record(output, clock.now_us(), origin, status);
+    }
+
+    /// Get the most recent entry of an output.
+    pub fn latest(&self, output: Output) -> Option<&Entry> {
+        self.buffers[output].recent()
+    }
+
+    /// Iterate over the entries of an output, oldest first.
+    pub fn entries(&self, output: Output) -> impl Iterator<Item = &Entry> {
+        self.buffers[output].oldest_ordered()
+    }
+
+    /// Iterate over the entries of an output recorded at or after the given timestamp, oldest
+    /// first.
+    pub fn since(&self, output: Output, timestamp_us: u64) -> impl Iterator<Item = &Entry> {
+        self.entries(output)
+            .filter(move |entry| entry.timestamp_us >= timestamp_us)
+    }
+
+    /// Forget all the entries of an output.
+    pub fn clear(&mut self, output: Output) {
+        self.buffers[output].clear();
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432",
+        idents(AsyncI2c(sync = "I2c"), AsyncErrorType(sync = "ErrorType"))
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T> AsyncDS4432<I, T> {
+    /// Give the driver a history recording every status written and read back, or remove it
+    ///
+    /// Statuses are only recorded once a clock is given with `set_clock`.
+    pub fn set_history(&mut self, history: Option<History<DRIVER_DEPTH>>) {
+        self.history = history;
+    }
+
+    /// Get the history of the driver, if recording
+    pub fn history(&self) -> Option<&History<DRIVER_DEPTH>> {
+        self.history.as_ref()
+    }
+
+    /// Take the history out of the driver, which stops recording
+    pub fn take_history(&mut self) -> Option<History<DRIVER_DEPTH>> {
+        self.history.take()
+    }
+
+    /// Record the register value of an output in the history
+    pub(crate) fn record_history(&mut self, output: Output, origin: Origin, value: u8) {
+        let status = self.decode(output, value);
+        if let (Some(history), Some(InstalledClock(clock))) =
+            (self.history.as_mut(), self.config.clock)
+        {
+            history.record(output, clock.now_us(), origin, status);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn history_overwrites_oldest_entries() {
+        let mut history = History::<2>::new();
+        history.record(Output::One, 1, Origin::Commanded, Status::Sink(1));
+        history.record(Output::One, 2, Origin::Commanded, Status::Sink(2));
+        history.record(Output::One, 3, Origin::Readback, Status::Sink(3));
+
+        let mut entries = history.entries(Output::One);
+        assert_eq!(entries.next().unwrap().status, Status::Sink(2));
+        assert_eq!(entries.next().unwrap().origin, Origin::Readback);
+        assert!(entries.next().is_none());
+        drop(entries);
+
+        history.clear(Output::One);
+        assert!(history.latest(Output::One).is_none());
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn driver_records_writes_and_readbacks() {
+        extern crate std;
+
+        use core::sync::atomic::{AtomicU64, Ordering};
+        use embedded_hal_mock::eh1::i2c;
+        use std::vec;
+
+        use crate::{DS4432, SLAVE_ADDRESS};
+
+        static NOW_US: AtomicU64 = AtomicU64::new(0);
+
+        struct TestClock;
+
+        impl Clock for TestClock {
+            fn now_us(&self) -> u64 {
+                NOW_US.load(Ordering::Relaxed)
+            }
+        }
+
+        let expectations = [
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x2A]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::One as u8], vec![0x2A]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x00]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::new(mock);
+        ds4432.set_clock(Some(&TestClock));
+        ds4432.set_history(Some(History::new()));
+
+        ds4432.set_status(Output::One, Status::Sink(42)).unwrap();
+        NOW_US.store(1_000, Ordering::Relaxed);
+        ds4432.disable(Output::One).unwrap();
+
+        let history = ds4432.take_history().unwrap();
+        let entries: std::vec::Vec<_> = history.entries(Output::One).copied().collect();
+        assert_eq!(
+            entries,
+            [
+                Entry {
+                    timestamp_us: 0,
+                    origin: Origin::Commanded,
+                    status: Status::Sink(42)
+                },
+                Entry {
+                    timestamp_us: 1_000,
+                    origin: Origin::Readback,
+                    status: Status::Sink(42)
+                },
+                Entry {
+                    timestamp_us: 1_000,
+                    origin: Origin::Commanded,
+                    status: Status::Disable
+                },
+            ]
+        );
+        assert!(ds4432.history().is_none());
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+}
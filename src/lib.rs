@@ -13,18 +13,92 @@
 #![no_std]
 #![macro_use]
 pub(crate) mod fmt;
+#[cfg(test)]
+#[macro_use]
+mod harness;
+
+#[cfg(any(feature = "std", feature = "tokio"))]
+extern crate std;
+
+use core::cmp::Ordering;
+use core::convert::Infallible;
+use delay::NoDelay;
+use event::{Access, Event, Verbosity};
+use fugit::MicrosDurationU32;
+use time::{Clock, InstalledClock};
 
 mod error;
 pub use error::{Error, Result};
 
+mod config;
+pub use config::Config;
+
+mod per_output;
+pub use per_output::PerOutput;
+
+#[cfg(feature = "async")]
+pub mod asynchronous;
+#[cfg(feature = "sync")]
+pub mod blocking;
+#[cfg(feature = "charge")]
+pub mod charge;
+#[cfg(feature = "control")]
+pub mod control;
+pub mod delay;
+pub mod derating;
+pub mod design;
+pub mod easing;
+#[cfg(any(test, feature = "emulator"))]
+pub mod emulator;
+pub mod event;
+pub mod fixed;
+#[cfg(feature = "history")]
+pub mod history;
+#[cfg(feature = "linux")]
+pub mod linux;
+#[cfg(feature = "logger")]
+pub mod logger;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+#[cfg(feature = "modbus")]
+pub mod modbus;
+#[cfg(feature = "pacing")]
+pub mod pacing;
+#[cfg(feature = "pulse")]
+pub mod pulse;
+#[cfg(feature = "redundant")]
+pub mod redundant;
+#[cfg(feature = "scpi")]
+pub mod scpi;
+#[cfg(feature = "script")]
+pub mod script;
+#[cfg(feature = "shell")]
+pub mod shell;
+#[cfg(feature = "state")]
+pub mod state;
+#[cfg(feature = "stress")]
+pub mod stress;
+pub mod time;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+pub mod transfer;
+
 #[cfg(not(any(feature = "sync", feature = "async")))]
 compile_error!("You should probably choose at least one of `sync` and `async` features.");
 
+#[cfg(feature = "async")]
+use crate::delay::AsyncDelaySource;
+#[cfg(feature = "sync")]
+use crate::delay::DelaySource;
+#[cfg(feature = "sync")]
+use embedded_hal::delay::DelayNs;
 #[cfg(feature = "sync")]
 use embedded_hal::i2c::ErrorType;
 #[cfg(feature = "sync")]
 use embedded_hal::i2c::I2c;
 #[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs as AsyncDelayNs;
+#[cfg(feature = "async")]
 use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
 #[cfg(feature = "async")]
 use embedded_hal_async::i2c::I2c as AsyncI2c;
@@ -33,16 +107,13 @@ use embedded_hal_async::i2c::I2c as AsyncI2c;
 #[cfg(any(feature = "async", feature = "sync"))]
 const SLAVE_ADDRESS: u8 = 0b1001000; // This is I2C address 0x48
 
-#[cfg(not(feature = "not-recommended-rfs"))]
 const RECOMMENDED_RFS_MIN: u32 = 40_000;
-#[cfg(not(feature = "not-recommended-rfs"))]
 const RECOMMENDED_RFS_MAX: u32 = 160_000;
 
-const IOUT_UA_MIN: f32 = 50.0;
 const IOUT_UA_MAX: f32 = 200.0;
 
 /// An output controllable by the DS4432. This device has two.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 #[repr(u8)]
 pub enum Output {
@@ -50,6 +121,19 @@ pub enum Output {
     One = 0xF9,
 }
 
+impl Output {
+    /// All the outputs of the device.
+    pub const ALL: [Output; 2] = [Output::Zero, Output::One];
+
+    /// The channel number of the output.
+    pub const fn index(self) -> usize {
+        match self {
+            Output::Zero => 0,
+            Output::One => 1,
+        }
+    }
+}
+
 impl From<Output> for u8 {
     fn from(value: Output) -> Self {
         value as u8
@@ -74,7 +158,8 @@ pub enum Status {
 
 impl Status {
     /// Return the raw DAC code for a given Status
-    /// MicroAmp variants return None because Rfs is unknown to make the conversion.
+    /// MicroAmp variants return None because Rfs is unknown to make the conversion, use
+    /// `code_with_rfs` instead.
     ///
     /// # Example
     /// ```
@@ -96,8 +181,33 @@ impl Status {
         }
     }
 
-    /// Convert a raw DAC code into its Current value in microamps according to the Rfs value.
-    /// MicroAmp variants return None because conversion is pointless.
+    /// Return the raw DAC code for a given Status according to the Rfs value.
+    /// MicroAmp variants are rounded down to the nearest code, codes and currents saturating at
+    /// 127.
+    ///
+    /// # Example
+    /// ```
+    /// use ds4432::Status;
+    ///
+    /// assert_eq!(Status::Sink(42).code_with_rfs(80_000), 42);
+    /// assert_eq!(Status::Sink(200).code_with_rfs(80_000), 127);
+    /// assert_eq!(Status::Disable.code_with_rfs(80_000), 0);
+    /// assert_eq!(Status::SinkMicroAmp(88.0).code_with_rfs(80_000), 112);
+    /// assert_eq!(Status::SourceMicroAmp(88.0).code_with_rfs(80_000), 112);
+    /// assert_eq!(Status::SourceMicroAmp(200.0).code_with_rfs(160_000), 127);
+    /// ```
+    pub fn code_with_rfs(&self, rfs_ohm: u32) -> u8 {
+        match self {
+            Self::SinkMicroAmp(i) | Self::SourceMicroAmp(i) => {
+                Scale::new(rfs_ohm).code_saturating(*i)
+            }
+            _ => self.code().unwrap_or_default().min(127),
+        }
+    }
+
+    /// Return the current value in microamps for a given Status according to the Rfs value.
+    /// Raw DAC codes are converted, saturating at 127, and MicroAmp variants are returned as is.
+    /// Returns None for a zero Rfs.
     ///
     /// # Example
     /// ```
@@ -107,12 +217,318 @@ impl Status {
     /// assert_eq!(Status::Source(42).current_ua(80_000), Some(32.71406));
     /// assert_eq!(Status::Sink(42).current_ua(80_000), Some(32.71406));
     /// assert_eq!(Status::Disable.current_ua(1000), Some(0.0));
-    /// assert_eq!(Status::SourceMicroAmp(42.0).current_ua(80_000), None);
-    /// assert_eq!(Status::SinkMicroAmp(42.0).current_ua(80_000), None);
+    /// assert_eq!(Status::SourceMicroAmp(42.0).current_ua(80_000), Some(42.0));
+    /// assert_eq!(Status::SinkMicroAmp(42.0).current_ua(80_000), Some(42.0));
+    /// assert_eq!(Status::Sink(42).current_ua(0), None);
     /// ```
     pub fn current_ua(&self, rfs_ohm: u32) -> Option<f32> {
-        self.code()
-            .map(|code| ((62_312.5 * code as f64) / (rfs_ohm as f64)) as f32)
+        if rfs_ohm == 0 {
+            return None;
+        }
+        Some(match self {
+            Self::SinkMicroAmp(i) | Self::SourceMicroAmp(i) => *i,
+            _ => Scale::new(rfs_ohm).current_ua(self.code().unwrap_or_default().min(127)),
+        })
+    }
+
+    /// Create a sink Status from a current value in microamps according to the Rfs value.
+    ///
+    /// The Rfs and current values are checked and the current is rounded down to the nearest
+    /// DAC code, so the returned Status is always a raw code one.
+    ///
+    /// # Example
+    /// ```
+    /// use ds4432::{Error, Status};
+    ///
+    /// assert_eq!(Status::sink_ua(80_000, 88.0), Ok(Status::Sink(112)));
+    /// assert_eq!(Status::sink_ua(80_000, 10.0), Ok(Status::Sink(12)));
+    /// assert_eq!(Status::sink_ua(80_000, -1.0), Err(Error::InvalidIout));
+    /// assert_eq!(Status::sink_ua(160_000, 200.0), Err(Error::InvalidIout));
+    /// assert_eq!(Status::sink_ua(0, 88.0), Err(Error::InvalidRfs { rfs_ohm: 0 }));
+    /// ```
+    pub fn sink_ua(rfs_ohm: u32, current_ua: f32) -> Result<Self, Infallible> {
+        Ok(Self::Sink(ua_to_code(rfs_ohm, current_ua)?))
+    }
+
+    /// Create a source Status from a current value in microamps according to the Rfs value.
+    ///
+    /// The Rfs and current values are checked and the current is rounded down to the nearest
+    /// DAC code, so the returned Status is always a raw code one.
+    ///
+    /// # Example
+    /// ```
+    /// use ds4432::{Error, Status};
+    ///
+    /// assert_eq!(Status::source_ua(80_000, 88.0), Ok(Status::Source(112)));
+    /// assert_eq!(Status::source_ua(80_000, 201.0), Err(Error::InvalidIout));
+    /// ```
+    pub fn source_ua(rfs_ohm: u32, current_ua: f32) -> Result<Self, Infallible> {
+        Ok(Self::Source(ua_to_code(rfs_ohm, current_ua)?))
+    }
+
+    /// Flip the direction of the Status, keeping its magnitude.
+    ///
+    /// # Example
+    /// ```
+    /// use ds4432::Status;
+    ///
+    /// assert_eq!(Status::Sink(42).invert(), Status::Source(42));
+    /// assert_eq!(Status::Source(42).invert(), Status::Sink(42));
+    /// assert_eq!(Status::SinkMicroAmp(42.0).invert(), Status::SourceMicroAmp(42.0));
+    /// assert_eq!(Status::SourceMicroAmp(42.0).invert(), Status::SinkMicroAmp(42.0));
+    /// assert_eq!(Status::Disable.invert(), Status::Disable);
+    /// ```
+    pub fn invert(&self) -> Self {
+        match *self {
+            Self::Disable => Self::Disable,
+            Self::Sink(c) => Self::Source(c),
+            Self::Source(c) => Self::Sink(c),
+            Self::SinkMicroAmp(i) => Self::SourceMicroAmp(i),
+            Self::SourceMicroAmp(i) => Self::SinkMicroAmp(i),
+        }
+    }
+
+    /// Scale the magnitude of the Status by the given factor.
+    /// Codes saturate at 127 and currents at the maximum Iout, a negative factor also inverts the
+    /// direction.
+    ///
+    /// # Example
+    /// ```
+    /// use ds4432::Status;
+    ///
+    /// assert_eq!(Status::Sink(42).scaled(0.5), Status::Sink(21));
+    /// assert_eq!(Status::Source(42).scaled(10.0), Status::Source(127));
+    /// assert_eq!(Status::Source(42).scaled(-1.0), Status::Sink(42));
+    /// assert_eq!(Status::SinkMicroAmp(60.0).scaled(1.5), Status::SinkMicroAmp(90.0));
+    /// assert_eq!(Status::SourceMicroAmp(150.0).scaled(2.0), Status::SourceMicroAmp(200.0));
+    /// assert_eq!(Status::Disable.scaled(2.0), Status::Disable);
+    /// ```
+    pub fn scaled(&self, factor: f32) -> Self {
+        let status = if factor < 0.0 { self.invert() } else { *self };
+        let factor = if factor < 0.0 { -factor } else { factor };
+
+        match status {
+            Self::Disable => Self::Disable,
+            // `as u8` saturates, min() keeps the code in the 7 bits range
+            Self::Sink(c) => Self::Sink(((c as f32 * factor) as u8).min(127)),
+            Self::Source(c) => Self::Source(((c as f32 * factor) as u8).min(127)),
+            Self::SinkMicroAmp(i) => Self::SinkMicroAmp((i * factor).min(IOUT_UA_MAX)),
+            Self::SourceMicroAmp(i) => Self::SourceMicroAmp((i * factor).min(IOUT_UA_MAX)),
+        }
+    }
+
+    /// Interpolate between two setpoints, `t` going from 0 (`from`) to 1 (`to`).
+    /// Raw codes are interpolated as signed codes rounded to the nearest one and currents as
+    /// signed currents, crossing zero when the direction changes. Setpoints mixing raw codes and
+    /// currents can't be interpolated: `from` is kept until `t` reaches 1.
+    ///
+    /// # Example
+    /// ```
+    /// use ds4432::Status;
+    ///
+    /// assert_eq!(Status::lerp(Status::Sink(10), Status::Source(20), 0.5), Status::Source(5));
+    /// assert_eq!(Status::lerp(Status::Sink(10), Status::Source(10), 0.5), Status::Disable);
+    /// assert_eq!(
+    ///     Status::lerp(Status::Disable, Status::SinkMicroAmp(100.0), 0.25),
+    ///     Status::SinkMicroAmp(25.0)
+    /// );
+    /// assert_eq!(Status::lerp(Status::Sink(10), Status::SinkMicroAmp(100.0), 0.5), Status::Sink(10));
+    /// ```
+    pub fn lerp(from: Self, to: Self, t: f32) -> Self {
+        if t >= 1.0 {
+            return to;
+        } else if t.partial_cmp(&0.0) != Some(Ordering::Greater) {
+            // also keeps `from` for a NaN `t`
+            return from;
+        }
+
+        let (start, end) = (Signed::from(&from), Signed::from(&to));
+        if let (Some(start), Some(end)) = (start.code(), end.code()) {
+            let code = start as f32 + (end - start) as f32 * t;
+            // `as` truncates toward zero
+            let code = if code < 0.0 { code - 0.5 } else { code + 0.5 } as i16;
+            signed_to_register(code).into()
+        } else if let (Some(start), Some(end)) = (start.micro_amp(), end.micro_amp()) {
+            let current = start + (end - start) * t;
+            if current > 0.0 {
+                Self::SourceMicroAmp(current)
+            } else if current < 0.0 {
+                Self::SinkMicroAmp(-current)
+            } else {
+                Self::Disable
+            }
+        } else {
+            from
+        }
+    }
+
+    /// Compare two statuses by their signed magnitude: sink is negative, source is positive and
+    /// all the zero values (`Disable`, `Sink(0)`, `Source(0)`, ...) are equal. Raw code and
+    /// microamps variants can't be compared to each other as the Rfs is unknown.
+    ///
+    /// # Example
+    /// ```
+    /// use core::cmp::Ordering;
+    /// use ds4432::Status;
+    ///
+    /// assert_eq!(Status::Sink(42).cmp_signed(&Status::Sink(10)), Some(Ordering::Less));
+    /// assert_eq!(Status::Sink(10).cmp_signed(&Status::Disable), Some(Ordering::Less));
+    /// assert_eq!(Status::Disable.cmp_signed(&Status::Source(10)), Some(Ordering::Less));
+    /// assert_eq!(
+    ///     Status::SinkMicroAmp(60.0).cmp_signed(&Status::SourceMicroAmp(60.0)),
+    ///     Some(Ordering::Less)
+    /// );
+    /// assert_eq!(Status::Sink(0).cmp_signed(&Status::Source(0)), Some(Ordering::Equal));
+    /// assert_eq!(Status::Sink(42).cmp_signed(&Status::SinkMicroAmp(42.0)), None);
+    /// ```
+    pub fn cmp_signed(&self, other: &Self) -> Option<Ordering> {
+        match (Signed::from(self), Signed::from(other)) {
+            (Signed::Zero, Signed::Zero) => Some(Ordering::Equal),
+            (Signed::Zero, Signed::Code(c)) => 0.partial_cmp(&c),
+            (Signed::Code(c), Signed::Zero) => c.partial_cmp(&0),
+            (Signed::Zero, Signed::MicroAmp(i)) => 0.0.partial_cmp(&i),
+            (Signed::MicroAmp(i), Signed::Zero) => i.partial_cmp(&0.0),
+            (Signed::Code(a), Signed::Code(b)) => a.partial_cmp(&b),
+            (Signed::MicroAmp(a), Signed::MicroAmp(b)) => a.partial_cmp(&b),
+            _ => None,
+        }
+    }
+}
+
+/// Check that a Rfs value is usable for microamps convertions.
+fn check_rfs<E>(rfs_ohm: u32) -> Result<(), E> {
+    #[cfg(feature = "not-recommended-rfs")]
+    if rfs_ohm == 0 {
+        return Err(Error::InvalidRfs { rfs_ohm });
+    }
+    #[cfg(not(feature = "not-recommended-rfs"))]
+    if !(RECOMMENDED_RFS_MIN..=RECOMMENDED_RFS_MAX).contains(&rfs_ohm) {
+        return Err(Error::InvalidRfs { rfs_ohm });
+    }
+    Ok(())
+}
+
+/// Convert a current value in microamps into a raw DAC code, rounded down.
+fn ua_to_code<E>(rfs_ohm: u32, current_ua: f32) -> Result<u8, E> {
+    check_rfs(rfs_ohm)?;
+    Scale::new(rfs_ohm).code(current_ua)
+}
+
+/// Convertion factors between microamps and DAC codes for a Rfs value, computed once so each
+/// convertion is a single multiply.
+///
+/// The factors are kept in double precision so the convertions round like the datasheet
+/// formula, `62312.5 * code / Rfs`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Scale {
+    ua_per_code: f64,
+    code_per_ua: f64,
+}
+
+impl Scale {
+    pub(crate) fn new(rfs_ohm: u32) -> Self {
+        Self {
+            ua_per_code: 62_312.5 / rfs_ohm as f64,
+            code_per_ua: rfs_ohm as f64 / 62_312.5,
+        }
+    }
+
+    /// Compute the factors of the outputs with a known Rfs, none with the `tiny` feature.
+    fn per_output(rfs_ohm: PerOutput<Option<u32>>) -> PerOutput<Option<Self>> {
+        #[cfg(not(feature = "tiny"))]
+        return rfs_ohm.map(|rfs| rfs.map(Self::new));
+        #[cfg(feature = "tiny")]
+        {
+            let _ = rfs_ohm;
+            PerOutput::default()
+        }
+    }
+
+    /// Convert a raw DAC code into microamps.
+    pub(crate) fn current_ua(self, code: u8) -> f32 {
+        (code as f64 * self.ua_per_code) as f32
+    }
+
+    /// Convert a current value in microamps into a raw DAC code, rounded down.
+    ///
+    /// Any current from 0 up to the full scale of the Rfs value, at code 127, is valid.
+    pub(crate) fn code<E>(self, current_ua: f32) -> Result<u8, E> {
+        // also rejects NaN
+        if !(0.0..).contains(&current_ua) {
+            return Err(Error::InvalidIout);
+        }
+        let code = current_ua as f64 * self.code_per_ua;
+        if code > 127.0 {
+            return Err(Error::InvalidIout);
+        }
+        Ok(code as u8)
+    }
+
+    /// Convert a current value in microamps into a raw DAC code, rounded down and saturating at
+    /// 127.
+    fn code_saturating(self, current_ua: f32) -> u8 {
+        // `as u8` saturates, min() keeps the code in the 7 bits range
+        ((current_ua as f64 * self.code_per_ua) as u8).min(127)
+    }
+}
+
+/// Convert a register value into a signed code, sink being negative.
+fn register_to_signed(value: u8) -> i16 {
+    let code = (value & 0x7F) as i16;
+    if value & 0x80 == 0x80 {
+        code
+    } else {
+        -code
+    }
+}
+
+/// Convert a signed code into a register value, sink being negative.
+fn signed_to_register(code: i16) -> u8 {
+    if code > 0 {
+        // ensures MSB is 1
+        code as u8 | 0x80
+    } else {
+        (-code) as u8
+    }
+}
+
+/// Signed magnitude of a Status used for comparisons.
+enum Signed {
+    Zero,
+    Code(i16),
+    MicroAmp(f32),
+}
+
+impl Signed {
+    /// The signed code, unless the Status is in microamps.
+    fn code(&self) -> Option<i16> {
+        match *self {
+            Self::Zero => Some(0),
+            Self::Code(c) => Some(c),
+            Self::MicroAmp(_) => None,
+        }
+    }
+
+    /// The signed current, unless the Status is a raw code.
+    fn micro_amp(&self) -> Option<f32> {
+        match *self {
+            Self::Zero => Some(0.0),
+            Self::Code(_) => None,
+            Self::MicroAmp(i) => Some(i),
+        }
+    }
+}
+
+impl From<&Status> for Signed {
+    fn from(value: &Status) -> Self {
+        match *value {
+            Status::Disable | Status::Sink(0) | Status::Source(0) => Self::Zero,
+            Status::Sink(c) => Self::Code(-(c as i16)),
+            Status::Source(c) => Self::Code(c as i16),
+            Status::SinkMicroAmp(i) | Status::SourceMicroAmp(i) if i == 0.0 => Self::Zero,
+            Status::SinkMicroAmp(i) => Self::MicroAmp(-i),
+            Status::SourceMicroAmp(i) => Self::MicroAmp(i),
+        }
     }
 }
 
@@ -130,22 +546,76 @@ impl From<u8> for Status {
     }
 }
 
+/// Convert a raw code Status into its register value.
+///
+/// # Example
+/// ```
+/// use ds4432::{Error, Status};
+///
+/// assert_eq!(u8::try_from(Status::Source(42)), Ok(0xAA));
+/// assert_eq!(u8::try_from(Status::Sink(42)), Ok(0x2A));
+/// assert_eq!(u8::try_from(Status::Source(0)), Ok(0x00));
+/// assert_eq!(
+///     u8::try_from(Status::Sink(128)),
+///     Err(Error::InvalidCode { code: 128, max: 127 })
+/// );
+/// assert_eq!(
+///     u8::try_from(Status::SinkMicroAmp(88.0)),
+///     Err(Error::CurrentWithoutRfs)
+/// );
+/// assert_eq!(Status::from(0xAA), Status::Source(42));
+/// ```
+impl TryFrom<Status> for u8 {
+    type Error = Error<Infallible>;
+
+    fn try_from(status: Status) -> Result<Self, Infallible> {
+        status_to_register(status)
+    }
+}
+
+/// Convert a raw code Status into its register value, the canonical encoding.
+fn status_to_register<E>(status: Status) -> Result<u8, E> {
+    match status {
+        Status::Disable | Status::Sink(0) | Status::Source(0) => Ok(0),
+        Status::Sink(code) | Status::Source(code) if code > 127 => {
+            Err(Error::InvalidCode { code, max: 127 })
+        }
+        Status::Sink(code) => Ok(code),
+        // ensures MSB is 1
+        Status::Source(code) => Ok(code | 0x80),
+        Status::SinkMicroAmp(_) | Status::SourceMicroAmp(_) => Err(Error::CurrentWithoutRfs),
+    }
+}
+
 /// A DS4432 Digital To Analog (DAC) converter on the I2C bus `I`.
 #[maybe_async_cfg::maybe(
     sync(feature = "sync", self = "DS4432"),
     async(feature = "async", keep_self)
 )]
-pub struct AsyncDS4432<I> {
+pub struct AsyncDS4432<I, T = NoDelay> {
     i2c: I,
-    rfs0_ohm: Option<u32>,
-    rfs1_ohm: Option<u32>,
+    config: Config,
+    /// Delay provider used through `DriverDelay`
+    delay: T,
+    /// Whether the last `toggle` of the outputs applied their first setpoint
+    toggled: PerOutput<bool>,
+    /// Statuses written to and read from the outputs, if recording
+    #[cfg(feature = "history")]
+    history: Option<history::History<{ history::DRIVER_DEPTH }>>,
+    /// Convertion factors of the outputs with a known Rfs
+    #[cfg_attr(feature = "tiny", allow(dead_code))]
+    scales: PerOutput<Option<Scale>>,
 }
 
 #[maybe_async_cfg::maybe(
     sync(
         feature = "sync",
         self = "DS4432",
-        idents(AsyncI2c(sync = "I2c"), AsyncErrorType(sync = "ErrorType"))
+        idents(
+            AsyncI2c(sync = "I2c"),
+            AsyncErrorType(sync = "ErrorType"),
+            AsyncDelaySource(sync = "DelaySource")
+        )
     ),
     async(feature = "async", keep_self)
 )]
@@ -173,119 +643,418 @@ impl<I: AsyncI2c + AsyncErrorType> AsyncDS4432<I> {
         rfs1_ohm: Option<u32>,
     ) -> Result<Self, I::Error> {
         for rfs in [rfs0_ohm, rfs1_ohm].into_iter().flatten() {
-            #[cfg(feature = "not-recommended-rfs")]
-            if rfs == 0 {
-                return Err(Error::InvalidRfs);
-            }
-            #[cfg(not(feature = "not-recommended-rfs"))]
-            if !(RECOMMENDED_RFS_MIN..=RECOMMENDED_RFS_MAX).contains(&rfs) {
-                return Err(Error::InvalidRfs);
-            }
+            check_rfs(rfs)?;
         }
         Ok(Self {
             i2c,
-            rfs0_ohm,
-            rfs1_ohm,
+            config: Config {
+                rfs_ohm: PerOutput::new(rfs0_ohm, rfs1_ohm),
+                labels: PerOutput::default(),
+                preserve_zero_polarity: false,
+                setpoints: PerOutput::default(),
+                derating: None,
+                derated_code: None,
+                verbosity: PerOutput::default(),
+                transfers: PerOutput::default(),
+                suspended: PerOutput::default(),
+                clock: None,
+                #[cfg(feature = "charge")]
+                meter: None,
+            },
+            delay: NoDelay,
+            toggled: PerOutput::default(),
+            #[cfg(feature = "history")]
+            history: None,
+            scales: Scale::per_output(PerOutput::new(rfs0_ohm, rfs1_ohm)),
         })
     }
 
-    /// Set the current sink/source status and code of an output
-    pub async fn set_status(&mut self, output: Output, status: Status) -> Result<(), I::Error> {
+    /// Create a DS4432 using the given I2C implementation and a configuration taken out of a
+    /// previous driver with `into_parts`.
+    pub fn from_parts(i2c: I, config: Config) -> Self {
+        trace!("from_parts");
+        Self {
+            i2c,
+            config,
+            delay: NoDelay,
+            toggled: PerOutput::default(),
+            #[cfg(feature = "history")]
+            history: None,
+            scales: Scale::per_output(config.rfs_ohm),
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432",
+        idents(
+            AsyncI2c(sync = "I2c"),
+            AsyncErrorType(sync = "ErrorType"),
+            AsyncDelaySource(sync = "DelaySource")
+        )
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T> AsyncDS4432<I, T> {
+    /// Set the current sink/source status and code of an output, returning the status actually
+    /// applied
+    ///
+    /// The applied status is the one the device is set to after rounding down to a DAC code and
+    /// derating, in microamps if the Rfs is known.
+    pub async fn set_status(&mut self, output: Output, status: Status) -> Result<Status, I::Error> {
         trace!("set_status");
 
-        let reg = output.into();
-        let value = match status {
-            Status::Disable | Status::Sink(0) | Status::Source(0) => 0,
-            Status::Sink(code) => {
-                if code > 127 {
-                    return Err(Error::InvalidCode(code));
-                } else {
-                    code
-                }
-            }
-            Status::Source(code) => {
-                if code > 127 {
-                    return Err(Error::InvalidCode(code));
-                } else {
-                    // ensures MSB is 1
-                    code | 0x80
-                }
-            }
-            Status::SinkMicroAmp(current) => {
-                if !(IOUT_UA_MIN..=IOUT_UA_MAX).contains(&current) {
-                    return Err(Error::InvalidIout);
-                }
-                let rfs = match output {
-                    Output::Zero => self.rfs0_ohm.ok_or(Error::UnknownRfs)?,
-                    Output::One => self.rfs1_ohm.ok_or(Error::UnknownRfs)?,
-                };
-                ((current * (rfs as f32)) / 62_312.5) as u8
+        let value = derating::clamp_register(self.encode(output, status)?, self.config.max_code());
+        self.write_register(output, value).await?;
+        Ok(self.decode(output, value))
+    }
+
+    /// Get the current sink/source status and code of an output
+    pub async fn status(&mut self, output: Output) -> Result<Status, I::Error> {
+        trace!("status");
+
+        let value = self.read_register(output).await?;
+        Ok(self.decode(output, value))
+    }
+
+    /// Read the current sink/source status and code of an output `samples` times, returning
+    /// the value most readings agree on
+    ///
+    /// Guards against corrupted bytes on noisy buses: fails with `Inconsistent` if no value is
+    /// read more than half of the times, and with `NoSamples` if `samples` is 0.
+    pub async fn status_robust(&mut self, output: Output, samples: u8) -> Result<Status, I::Error> {
+        trace!("status_robust");
+
+        if samples == 0 {
+            return Err(Error::NoSamples);
+        }
+        // occurrences of each register value
+        let mut tally = [0u8; 256];
+        let mut majority = 0;
+        for _ in 0..samples {
+            let value = self.read_register(output).await?;
+            tally[value as usize] += 1;
+            if tally[value as usize] > tally[majority as usize] {
+                majority = value;
             }
+        }
+        if tally[majority as usize] < samples && self.config.verbosity[output].warns() {
+            warn!("{:?} readings disagree", output);
+        }
+        if tally[majority as usize] > samples / 2 {
+            Ok(self.decode(output, majority))
+        } else {
+            Err(Error::Inconsistent { output })
+        }
+    }
+
+    /// Disable an output, remembering its status so `enable` can restore it
+    ///
+    /// Disabling an output that is already disabled keeps the status remembered the first time.
+    pub async fn disable(&mut self, output: Output) -> Result<(), I::Error> {
+        trace!("disable");
+
+        let value = match self.config.suspended[output] {
+            Some(value) => value,
+            None => self.read_register(output).await?,
+        };
+        self.write_register(output, 0x00).await?;
+        self.config.suspended[output] = Some(value);
+        Ok(())
+    }
+
+    /// Restore the status an output had before `disable`
+    ///
+    /// Does nothing if the output wasn't disabled with `disable`, or if another status was set
+    /// since.
+    pub async fn enable(&mut self, output: Output) -> Result<(), I::Error> {
+        trace!("enable");
+
+        match self.config.suspended[output] {
+            Some(value) => self.write_register(output, value).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Set the current sink/source status and code of an output, then read it back to make sure
+    /// the device holds the written value
+    pub async fn set_status_verified(
+        &mut self,
+        output: Output,
+        status: Status,
+    ) -> Result<(), I::Error> {
+        trace!("set_status_verified");
+
+        let expected =
+            derating::clamp_register(self.encode(output, status)?, self.config.max_code());
+        self.write_register(output, expected).await?;
+        let actual = self.read_register(output).await?;
+        if actual != expected {
+            return Err(Error::Verify {
+                output,
+                expected,
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    /// Move an output from its current status to the target one over the given duration, one
+    /// DAC code at a time, crossing zero if the direction changes
+    pub async fn ramp_to<D: AsyncDelaySource<T>>(
+        &mut self,
+        output: Output,
+        target: Status,
+        duration: MicrosDurationU32,
+        delay: &mut D,
+    ) -> Result<(), I::Error> {
+        trace!("ramp_to");
+
+        self.ramp_to_eased(output, target, duration, &easing::Linear, delay)
+            .await
+    }
+
+    /// Configure the two statuses `toggle` flips an output between (e.g. margin-high and
+    /// margin-low)
+    pub fn set_setpoints(
+        &mut self,
+        output: Output,
+        first: Status,
+        second: Status,
+    ) -> Result<(), I::Error> {
+        self.config.setpoints[output] =
+            Some([self.encode(output, first)?, self.encode(output, second)?]);
+        self.toggled[output] = false;
+        Ok(())
+    }
+
+    /// Apply the other setpoint of an output, starting with the first one
+    pub async fn toggle(&mut self, output: Output) -> Result<(), I::Error> {
+        trace!("toggle");
+
+        let setpoints = self.config.setpoints[output].ok_or(Error::UnknownSetpoints { output })?;
+        let toggled = !self.toggled[output];
+        self.write_register(output, setpoints[if toggled { 0 } else { 1 }])
+            .await?;
+        self.toggled[output] = toggled;
+        Ok(())
+    }
+
+    /// Toggle an output `count` times, waiting `period` after each toggle
+    pub async fn toggle_repeat<D: AsyncDelaySource<T>>(
+        &mut self,
+        output: Output,
+        count: u32,
+        period: MicrosDurationU32,
+        delay: &mut D,
+    ) -> Result<(), I::Error> {
+        trace!("toggle_repeat");
+
+        for _ in 0..count {
+            self.toggle(output).await?;
+            delay
+                .provider(&mut self.delay)
+                .delay_us(period.ticks())
+                .await;
+        }
+        Ok(())
+    }
+
+    /// Convert a Status into the register value of an output
+    pub(crate) fn encode(&self, output: Output, status: Status) -> Result<u8, I::Error> {
+        Ok(match status {
+            // ensures MSB is 1
+            Status::Source(0) if self.config.preserve_zero_polarity => 0x80,
+            Status::SinkMicroAmp(current) => self.scale(output)?.code(current)?,
             Status::SourceMicroAmp(current) => {
-                if !(IOUT_UA_MIN..=IOUT_UA_MAX).contains(&current) {
-                    return Err(Error::InvalidIout);
-                }
-                let rfs = match output {
-                    Output::Zero => self.rfs0_ohm.ok_or(Error::UnknownRfs)?,
-                    Output::One => self.rfs1_ohm.ok_or(Error::UnknownRfs)?,
-                };
                 // ensures MSB is 1
-                ((current * (rfs as f32)) / 62_312.5) as u8 | 0x80
+                self.scale(output)?.code(current)? | 0x80
             }
+            _ => status_to_register(status)?,
+        })
+    }
+
+    /// Convert the register value of an output into a Status, in microamps if the Rfs is known
+    pub(crate) fn decode(&self, output: Output, value: u8) -> Status {
+        let status = match value {
+            0x80 if self.config.preserve_zero_polarity => Status::Source(0),
+            _ => value.into(),
         };
+        match (self.scale(output).ok(), status) {
+            (Some(scale), Status::Sink(code)) => Status::SinkMicroAmp(scale.current_ua(code)),
+            (Some(scale), Status::Source(code)) => Status::SourceMicroAmp(scale.current_ua(code)),
+            _ => status,
+        }
+    }
 
-        debug!("W @0x{:x}={:x}", reg, value);
+    /// Write the raw register value of an output
+    pub(crate) async fn write_register(
+        &mut self,
+        output: Output,
+        value: u8,
+    ) -> Result<(), I::Error> {
+        let value = derating::clamp_register(value, self.config.max_code());
+        let mut event = self.event(Access::Write, output, value);
+        let result = self.i2c.write(SLAVE_ADDRESS, &[output.into(), value]).await;
 
-        self.i2c
-            .write(SLAVE_ADDRESS, &[reg, value])
-            .await
-            .map_err(Error::I2c)
+        event.ok = result.is_ok();
+        if event.ok {
+            // a new status replaces the one remembered by `disable`
+            self.config.suspended[output] = None;
+            #[cfg(feature = "charge")]
+            self.record_charge(output, value);
+            #[cfg(feature = "history")]
+            self.record_history(output, history::Origin::Commanded, value);
+        }
+        self.log_event(&event);
+        result.map_err(Error::I2c)
     }
 
-    /// Get the current sink/source status and code of an output
-    pub async fn status(&mut self, output: Output) -> Result<Status, I::Error> {
-        trace!("status");
-
+    /// Read the raw register value of an output
+    pub(crate) async fn read_register(&mut self, output: Output) -> Result<u8, I::Error> {
         let mut buf = [0x00];
-        let reg = output.into();
+        let result = self
+            .i2c
+            .write_read(SLAVE_ADDRESS, &[output.into()], &mut buf)
+            .await;
 
-        self.i2c
-            .write_read(SLAVE_ADDRESS, &[reg], &mut buf)
-            .await
-            .map_err(Error::I2c)?;
-
-        debug!("R @0x{:x}={:x}", reg, buf[0]);
-
-        let mut status = buf[0].into();
-        match output {
-            Output::Zero => {
-                if let Some(rfs) = self.rfs0_ohm {
-                    status = match status {
-                        Status::Sink(code) => {
-                            Status::SinkMicroAmp(Status::Sink(code).current_ua(rfs).unwrap())
-                        }
-                        Status::Source(code) => {
-                            Status::SourceMicroAmp(Status::Source(code).current_ua(rfs).unwrap())
-                        }
-                        _ => status,
-                    }
-                }
-            }
-            Output::One => {
-                if let Some(rfs) = self.rfs1_ohm {
-                    status = match status {
-                        Status::Sink(code) => {
-                            Status::SinkMicroAmp(Status::Sink(code).current_ua(rfs).unwrap())
-                        }
-                        Status::Source(code) => {
-                            Status::SourceMicroAmp(Status::Source(code).current_ua(rfs).unwrap())
-                        }
-                        _ => status,
-                    }
-                }
-            }
+        let mut event = self.event(Access::Read, output, buf[0]);
+        event.ok = result.is_ok();
+        #[cfg(feature = "history")]
+        if event.ok {
+            self.record_history(output, history::Origin::Readback, buf[0]);
+        }
+        self.log_event(&event);
+        result.map_err(Error::I2c).map(|_| buf[0])
+    }
+
+    /// Log a register access, if allowed by the verbosity of its output
+    fn log_event(&self, event: &Event) {
+        if !self.config.verbosity[event.output].logs(event) {
+            return;
+        }
+        if event.ok {
+            debug!("{}", event);
+        } else {
+            error!("{}", event);
+        }
+    }
+
+    /// Describe a register access for the logs
+    fn event(&self, access: Access, output: Output, value: u8) -> Event {
+        let mut event = Event::new(access, output, value, self.scale(output).ok());
+        event.label = self.label(output);
+        event
+    }
+
+    /// Attach a name to an output (e.g. "VCORE trim"), used in log lines to tell the channels apart
+    pub fn set_label(&mut self, output: Output, label: &'static str) {
+        self.config.labels[output] = Some(label);
+    }
+
+    /// Get the name attached to an output, if any
+    pub fn label(&self, output: Output) -> Option<&'static str> {
+        self.config.labels[output]
+    }
+
+    /// Give the driver a clock, timestamping what it records about the outputs, or remove it
+    pub fn set_clock(&mut self, clock: Option<&'static (dyn Clock + Sync)>) {
+        self.config.clock = clock.map(InstalledClock);
+    }
+
+    /// Choose what is logged about an output, its warnings and every register access by default
+    ///
+    /// # Example
+    #[cfg_attr(feature = "sync", doc = "```")]
+    #[cfg_attr(not(feature = "sync"), doc = "```ignore")]
+    /// # use embedded_hal_mock::eh1::i2c;
+    /// use ds4432::event::Verbosity;
+    /// use ds4432::{Output, DS4432};
+    ///
+    /// # let i2c = i2c::Mock::new(&[]);
+    /// let mut ds4432 = DS4432::new(i2c);
+    /// // follow the tuning loop on OUT0, only report the failures of OUT1
+    /// ds4432.set_verbosity(Output::One, Verbosity::Error);
+    /// assert_eq!(ds4432.config().verbosity(Output::Zero), Verbosity::Debug);
+    /// # ds4432.release().done();
+    /// ```
+    pub fn set_verbosity(&mut self, output: Output, verbosity: Verbosity) {
+        self.config.verbosity[output] = verbosity;
+    }
+
+    /// Get the convertion factors of an output, if its Rfs is known
+    ///
+    /// With the `tiny` feature, the microamps convertions are never available.
+    fn scale(&self, output: Output) -> Result<Scale, I::Error> {
+        #[cfg(not(feature = "tiny"))]
+        return self.scales[output].ok_or(Error::UnknownRfs { output });
+        #[cfg(feature = "tiny")]
+        return Err(Error::UnknownRfs { output });
+    }
+
+    /// Return the underlying I2C device and the driver configuration, to rebuild the driver
+    /// later with `from_parts`
+    pub fn into_parts(self) -> (I, Config) {
+        (self.i2c, self.config)
+    }
+
+    /// Write `Source(0)` as 0x80 instead of 0x00 so the source direction is kept by the device,
+    /// and read 0x80 back as `Source(0)` instead of `Disable`
+    pub fn set_preserve_zero_polarity(&mut self, preserve: bool) {
+        self.config.preserve_zero_polarity = preserve;
+    }
+
+    /// Get the driver configuration
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Give the driver a delay provider, used by the time-based methods given `DriverDelay`
+    pub fn with_delay<U>(self, delay: U) -> AsyncDS4432<I, U> {
+        AsyncDS4432 {
+            i2c: self.i2c,
+            config: self.config,
+            delay,
+            toggled: self.toggled,
+            #[cfg(feature = "history")]
+            history: self.history,
+            scales: self.scales,
         }
-        Ok(status)
+    }
+
+    /// Get the delay provider given with `with_delay`
+    pub fn delay_mut(&mut self) -> &mut T {
+        &mut self.delay
+    }
+
+    /// Borrow the underlying I2C device, e.g. to reach other devices on the same bus
+    ///
+    /// # Example
+    #[cfg_attr(feature = "sync", doc = "```")]
+    #[cfg_attr(not(feature = "sync"), doc = "```ignore")]
+    /// # use embedded_hal_mock::eh1::i2c;
+    /// use ds4432::{Output, Status, DS4432};
+    /// use embedded_hal::i2c::I2c;
+    ///
+    /// # let i2c = i2c::Mock::new(&[
+    /// #     i2c::Transaction::write(0x48, vec![0xF8, 0x2A]),
+    /// #     i2c::Transaction::write(0x50, vec![0x00, 0x2A]),
+    /// # ]);
+    /// let mut ds4432 = DS4432::new(i2c);
+    /// ds4432.set_status(Output::Zero, Status::Sink(42)).unwrap();
+    /// // store the code in the EEPROM next to the DAC
+    /// ds4432.bus_mut().write(0x50, &[0x00, 0x2A]).unwrap();
+    /// # ds4432.release().done();
+    /// ```
+    pub fn bus_mut(&mut self) -> &mut I {
+        &mut self.i2c
+    }
+
+    /// Run a closure with the underlying I2C device borrowed, returning its result
+    pub fn with_bus<R>(&mut self, f: impl FnOnce(&mut I) -> R) -> R {
+        f(&mut self.i2c)
     }
 
     /// Return the underlying I2C device
@@ -299,14 +1068,24 @@ impl<I: AsyncI2c + AsyncErrorType> AsyncDS4432<I> {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "sync"))]
 mod test {
     // extern crate alloc;
     extern crate std;
 
     use super::*;
     use embedded_hal_mock::eh1::i2c;
+    use fugit::ExtU32;
     use std::vec;
+    use time::{Clock, MockClock, MockDelay};
+
+    #[test]
+    fn register_round_trips_through_status() {
+        for value in 0..=u8::MAX {
+            let expected = if value == 0x80 { 0x00 } else { value };
+            assert_eq!(u8::try_from(Status::from(value)), Ok(expected));
+        }
+    }
 
     #[test]
     fn u8_to_status_conversion() {
@@ -349,6 +1128,240 @@ mod test {
         mock.done();
     }
 
+    #[test]
+    fn can_label_outputs() {
+        let mock = i2c::Mock::new(&[]);
+        let mut ds4432 = DS4432::new(mock);
+
+        ds4432.set_label(Output::One, "VCORE trim");
+        assert_eq!(ds4432.label(Output::Zero), None);
+        assert_eq!(ds4432.label(Output::One), Some("VCORE trim"));
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[test]
+    fn can_ramp_output_0_across_zero() {
+        let expectations = [
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0x02]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x01]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x00]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x81]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::new(mock);
+        let clock = MockClock::new();
+
+        ds4432
+            .ramp_to(
+                Output::Zero,
+                Status::Source(1),
+                300.micros(),
+                &mut MockDelay::new(&clock),
+            )
+            .unwrap();
+        assert_eq!(clock.now_us(), 300);
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[test]
+    fn set_status_verified_reports_mismatch() {
+        let expectations = [
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x2A]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::One as u8], vec![0x2B]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::new(mock);
+
+        assert_eq!(
+            ds4432.set_status_verified(Output::One, Status::Sink(42)),
+            Err(Error::Verify {
+                output: Output::One,
+                expected: 0x2A,
+                actual: 0x2B
+            })
+        );
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[cfg(not(feature = "tiny"))]
+    #[test]
+    fn keeps_config_across_parts() {
+        let expectations = [
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0xAA]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::One as u8], vec![0x00]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0xAA]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::with_rfs(mock, Some(80_000), None).unwrap();
+        ds4432.set_label(Output::Zero, "VCORE trim");
+        ds4432.set_derating(Some(derating::DeratingCurve::new(&[(25, 127), (75, 77)])));
+        ds4432.set_temperature(75.0).unwrap();
+
+        let (mock, config) = ds4432.into_parts();
+        assert_eq!(config.rfs_ohm(Output::Zero), Some(80_000));
+        let mut ds4432 = DS4432::from_parts(mock, config);
+
+        assert_eq!(ds4432.label(Output::Zero), Some("VCORE trim"));
+        assert_eq!(ds4432.max_code(), 77);
+        let status = ds4432.status(Output::Zero).unwrap();
+        assert_eq!(status, Status::SourceMicroAmp(32.71406));
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[test]
+    fn shares_the_bus_with_other_devices() {
+        use embedded_hal::i2c::I2c;
+
+        let expectations = [
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x2A]),
+            i2c::Transaction::write(0x50, vec![0x00, 0x2A]),
+            i2c::Transaction::write_read(0x50, vec![0x00], vec![0x2A]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::new(mock);
+
+        ds4432.set_status(Output::Zero, Status::Sink(42)).unwrap();
+        ds4432.bus_mut().write(0x50, &[0x00, 0x2A]).unwrap();
+        let mut buf = [0x00];
+        ds4432
+            .with_bus(|bus| bus.write_read(0x50, &[0x00], &mut buf))
+            .unwrap();
+        assert_eq!(buf, [0x2A]);
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[test]
+    fn enable_restores_status_before_disable() {
+        let expectations = [
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::One as u8], vec![0x2A]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x00]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x00]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x2A]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::new(mock);
+
+        ds4432.disable(Output::One).unwrap();
+        ds4432.disable(Output::One).unwrap();
+        ds4432.enable(Output::One).unwrap();
+        ds4432.enable(Output::One).unwrap();
+        ds4432.enable(Output::Zero).unwrap();
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[test]
+    fn enable_restores_status_across_parts() {
+        let expectations = [
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0xAA]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x00]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0xAA]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::new(mock);
+
+        ds4432.disable(Output::Zero).unwrap();
+        let (mock, config) = ds4432.into_parts();
+        assert_eq!(config.suspended(Output::Zero), Some(Status::Source(42)));
+        let mut ds4432 = DS4432::from_parts(mock, config);
+        ds4432.enable(Output::Zero).unwrap();
+        assert_eq!(ds4432.config().suspended(Output::Zero), None);
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[test]
+    fn robust_status_takes_the_majority() {
+        let read = |value| {
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![value])
+        };
+        let mut expectations = vec![read(0x2A), read(0xAA), read(0x2A), read(0x2A), read(0xAA)];
+        // a majority among more distinct values than the majority count
+        expectations.extend([0x01, 0x02, 0x03, 0x04, 0x05, 0x06].map(read));
+        expectations.extend([0x07; 7].map(read));
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::new(mock);
+
+        assert_eq!(ds4432.status_robust(Output::Zero, 0), Err(Error::NoSamples));
+        assert_eq!(
+            ds4432.status_robust(Output::Zero, 3).unwrap(),
+            Status::Sink(42)
+        );
+        assert_eq!(
+            ds4432.status_robust(Output::Zero, 2),
+            Err(Error::Inconsistent {
+                output: Output::Zero
+            })
+        );
+        assert_eq!(
+            ds4432.status_robust(Output::Zero, 13).unwrap(),
+            Status::Sink(7)
+        );
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[test]
+    fn toggles_between_setpoints() {
+        let expectations = [
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x8A]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x06]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x8A]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::new(mock);
+        let clock = MockClock::new();
+
+        assert_eq!(
+            ds4432.toggle(Output::Zero),
+            Err(Error::UnknownSetpoints {
+                output: Output::Zero
+            })
+        );
+        ds4432
+            .set_setpoints(Output::Zero, Status::Source(10), Status::Sink(6))
+            .unwrap();
+        ds4432
+            .toggle_repeat(Output::Zero, 3, 50.micros(), &mut MockDelay::new(&clock))
+            .unwrap();
+        assert_eq!(clock.now_us(), 150);
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[test]
+    fn can_preserve_zero_polarity() {
+        let expectations = [
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x80]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::One as u8], vec![0x80]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::new(mock);
+        ds4432.set_preserve_zero_polarity(true);
+
+        ds4432.set_status(Output::One, Status::Source(0)).unwrap();
+        let status = ds4432.status(Output::One).unwrap();
+        assert_eq!(status, Status::Source(0));
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[cfg(not(feature = "tiny"))]
     #[test]
     fn can_get_output_0_status_current() {
         let expectations = [i2c::Transaction::write_read(
@@ -366,6 +1379,7 @@ mod test {
         mock.done();
     }
 
+    #[cfg(not(feature = "tiny"))]
     #[test]
     fn can_set_output_1_status_current() {
         let expectations = [i2c::Transaction::write(
@@ -375,10 +1389,11 @@ mod test {
         let mock = i2c::Mock::new(&expectations);
         let mut ds4432 = DS4432::with_rfs(mock, None, Some(80_000)).unwrap();
 
-        // just making sure it doesn't error
-        ds4432
+        // rounded down to code 112
+        let applied = ds4432
             .set_status(Output::One, Status::SinkMicroAmp(88.0))
             .unwrap();
+        assert_eq!(applied, Status::SinkMicroAmp(87.2375));
 
         let mut mock = ds4432.release();
         mock.done();
@@ -0,0 +1,175 @@
+//! Device discovery on Linux hosts.
+//!
+//! Locates the DS4432 without a hard-coded `/dev/i2c-N` path, with the kernel sysfs:
+//! - [`find`] lists the devices declared in the device tree or instantiated on a bus, whose
+//!   driver name contains "ds4432".
+//! - [`adapters`] lists the I2C buses exposed as `/dev/i2c-N`, and [`scan`] probes each of them
+//!   for an acknowledging DS4432 when nothing is declared.
+//!
+//! # Example
+//! ```no_run
+//! use ds4432::linux;
+//!
+//! for location in linux::find().unwrap() {
+//!     println!("DS4432 at {:#04x} on {}", location.address, location.dev_path().display());
+//! }
+//! ```
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+
+#[cfg(feature = "sync")]
+use crate::{Output, SLAVE_ADDRESS};
+
+/// Where the sysfs is usually mounted.
+const SYSFS: &str = "/sys";
+
+/// The bus and address of a device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Location {
+    /// The adapter number, `N` in `/dev/i2c-N`
+    pub bus: u32,
+    /// The 7-bit I2C address
+    pub address: u8,
+}
+
+impl Location {
+    /// Get the character device of the bus, to open with e.g. `linux_embedded_hal::I2cdev`.
+    pub fn dev_path(&self) -> PathBuf {
+        PathBuf::from(std::format!("/dev/i2c-{}", self.bus))
+    }
+}
+
+/// List the DS4432 declared on the I2C buses.
+pub fn find() -> io::Result<Vec<Location>> {
+    find_in(Path::new(SYSFS))
+}
+
+/// List the I2C adapter numbers exposed in `/dev`.
+pub fn adapters() -> io::Result<Vec<u32>> {
+    adapters_in(Path::new(SYSFS))
+}
+
+/// Find the DS4432 in a sysfs mounted at `sysfs`.
+fn find_in(sysfs: &Path) -> io::Result<Vec<Location>> {
+    let mut locations = Vec::new();
+    for entry in fs::read_dir(sysfs.join("bus/i2c/devices"))? {
+        let entry = entry?;
+        // devices are named `<bus>-<address as 4 hex digits>`, adapters `i2c-<bus>`
+        let name = entry.file_name();
+        let location = name.to_str().and_then(|name| {
+            let (bus, address) = name.split_once('-')?;
+            Some(Location {
+                bus: bus.parse().ok()?,
+                address: u16::from_str_radix(address, 16).ok()?.try_into().ok()?,
+            })
+        });
+        let location = match location {
+            Some(location) => location,
+            None => continue,
+        };
+        let declared = ["name", "of_node/compatible"].iter().any(|file| {
+            fs::read(entry.path().join(file)).is_ok_and(|content| {
+                content
+                    .to_ascii_lowercase()
+                    .windows(6)
+                    .any(|window| window == b"ds4432")
+            })
+        });
+        if declared {
+            locations.push(location);
+        }
+    }
+    locations.sort_by_key(|location| (location.bus, location.address));
+    Ok(locations)
+}
+
+/// List the I2C adapters of a sysfs mounted at `sysfs`.
+fn adapters_in(sysfs: &Path) -> io::Result<Vec<u32>> {
+    let mut buses = Vec::new();
+    for entry in fs::read_dir(sysfs.join("class/i2c-dev"))? {
+        let name = entry?.file_name();
+        if let Some(bus) = name.to_str().and_then(|name| name.strip_prefix("i2c-")) {
+            if let Ok(bus) = bus.parse() {
+                buses.push(bus);
+            }
+        }
+    }
+    buses.sort_unstable();
+    Ok(buses)
+}
+
+/// Whether a DS4432 acknowledges a register read on the bus.
+#[cfg(feature = "sync")]
+pub fn probe<I: I2c>(i2c: &mut I) -> bool {
+    let mut buf = [0x00];
+    i2c.write_read(SLAVE_ADDRESS, &[Output::Zero.into()], &mut buf)
+        .is_ok()
+}
+
+/// Probe every I2C adapter for a DS4432, opening the buses with `open`
+/// (e.g. `linux_embedded_hal::I2cdev::new`).
+///
+/// Buses failing to open are skipped. Probing writes a register address to whatever answers at
+/// the DS4432 address, so prefer [`find`] when the device is declared.
+#[cfg(feature = "sync")]
+pub fn scan<I: I2c, E>(mut open: impl FnMut(PathBuf) -> Result<I, E>) -> io::Result<Vec<Location>> {
+    let mut locations = Vec::new();
+    for bus in adapters()? {
+        let location = Location {
+            bus,
+            address: SLAVE_ADDRESS,
+        };
+        if let Ok(mut i2c) = open(location.dev_path()) {
+            if probe(&mut i2c) {
+                locations.push(location);
+            }
+        }
+    }
+    Ok(locations)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::vec;
+
+    #[test]
+    fn finds_declared_devices() {
+        let sysfs = std::env::temp_dir().join(std::format!("ds4432-sysfs-{}", std::process::id()));
+        let devices = sysfs.join("bus/i2c/devices");
+        for (device, name) in [("1-0048", "ds4432\n"), ("1-0050", "24c02\n"), ("i2c-1", "")] {
+            fs::create_dir_all(devices.join(device)).unwrap();
+            fs::write(devices.join(device).join("name"), name).unwrap();
+        }
+        fs::create_dir_all(devices.join("3-0049/of_node")).unwrap();
+        fs::write(devices.join("3-0049/of_node/compatible"), "maxim,DS4432\0").unwrap();
+        for adapter in ["i2c-3", "i2c-1"] {
+            fs::create_dir_all(sysfs.join("class/i2c-dev").join(adapter)).unwrap();
+        }
+
+        let found = find_in(&sysfs);
+        let buses = adapters_in(&sysfs);
+        fs::remove_dir_all(&sysfs).unwrap();
+        assert_eq!(
+            found.unwrap(),
+            vec![
+                Location {
+                    bus: 1,
+                    address: 0x48
+                },
+                Location {
+                    bus: 3,
+                    address: 0x49
+                }
+            ]
+        );
+        assert_eq!(buses.unwrap(), vec![1, 3]);
+    }
+}
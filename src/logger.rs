@@ -0,0 +1,261 @@
+//! CSV status logger.
+//!
+//! Captures timestamped channel statuses for long-duration trim stability tests. Each sample is
+//! a CSV line written into any [`core::fmt::Write`] (a UART, a file on an SD card, ...), with
+//! the signed code of each output (negative codes sink) and its current in microamps when the
+//! Rfs is known:
+//!
+//! ```text
+//! timestamp_us,out0_code,out0_ua,out1_code,out1_ua
+//! 1000000,42,32.714,-5,
+//! ```
+//!
+//! With the `embedded-io` feature, `IoWriter` adapts `embedded_io::Write` writers.
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! # use embedded_hal_mock::eh1::i2c;
+//! use ds4432::logger::CsvLogger;
+//! use ds4432::time::MockClock;
+//! use ds4432::DS4432;
+//! use fugit::ExtU64;
+//!
+//! # #[cfg(not(feature = "tiny"))] {
+//! # let i2c = i2c::Mock::new(&[
+//! #     i2c::Transaction::write_read(0x48, vec![0xF8], vec![0xAA]),
+//! #     i2c::Transaction::write_read(0x48, vec![0xF9], vec![0x05]),
+//! # ]);
+//! let mut ds4432 = DS4432::with_rfs(i2c, Some(80_000), None).unwrap();
+//! let mut logger = CsvLogger::new(String::new());
+//! let clock = MockClock::new();
+//! clock.advance(1.secs());
+//!
+//! ds4432.log_csv(&mut logger, &clock).unwrap();
+//! assert_eq!(
+//!     logger.release(),
+//!     "timestamp_us,out0_code,out0_ua,out1_code,out1_ua\n1000000,42,32.714,-5,\n"
+//! );
+//! # ds4432.release().done();
+//! # }
+//! ```
+
+use core::fmt::Write;
+
+use fugit::MicrosDurationU32;
+
+#[cfg(feature = "async")]
+use crate::delay::AsyncDelaySource;
+#[cfg(feature = "sync")]
+use crate::delay::DelaySource;
+#[cfg(feature = "sync")]
+use crate::DS4432;
+#[cfg(feature = "sync")]
+use embedded_hal::delay::DelayNs;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::ErrorType;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs as AsyncDelayNs;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+use crate::state::DeviceState;
+use crate::time::Clock;
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+use crate::{register_to_signed, Error, Output, PerOutput};
+
+/// Why a sample can't be logged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum LogError<E> {
+    /// The CSV line can't be written
+    Write,
+    /// The driver failed to read the outputs
+    Device(Error<E>),
+}
+
+impl<E> From<Error<E>> for LogError<E> {
+    fn from(error: Error<E>) -> Self {
+        Self::Device(error)
+    }
+}
+
+/// Writes timestamped channel statuses as CSV lines into `W`.
+///
+/// The CSV header is written before the first line.
+#[derive(Debug)]
+pub struct CsvLogger<W> {
+    writer: W,
+    header: bool,
+}
+
+impl<W: Write> CsvLogger<W> {
+    /// Create a logger writing into the given writer.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            header: false,
+        }
+    }
+
+    /// Write a sample line, `current_ua` being known for the outputs with a Rfs value.
+    pub fn record(
+        &mut self,
+        timestamp_us: u64,
+        state: &DeviceState,
+        current_ua: PerOutput<Option<f32>>,
+    ) -> core::fmt::Result {
+        if !self.header {
+            writeln!(
+                self.writer,
+                "timestamp_us,out0_code,out0_ua,out1_code,out1_ua"
+            )?;
+            self.header = true;
+        }
+        write!(self.writer, "{}", timestamp_us)?;
+        for output in Output::ALL {
+            write!(
+                self.writer,
+                ",{}",
+                register_to_signed(state.register(output))
+            )?;
+            match current_ua[output] {
+                Some(current) => write!(self.writer, ",{:.3}", current)?,
+                None => self.writer.write_char(',')?,
+            }
+        }
+        writeln!(self.writer)
+    }
+
+    /// Return the underlying writer
+    pub fn release(self) -> W {
+        self.writer
+    }
+}
+
+/// An `embedded_io::Write` writer usable as a [`core::fmt::Write`] one.
+#[cfg(feature = "embedded-io")]
+#[derive(Debug)]
+pub struct IoWriter<W>(pub W);
+
+#[cfg(feature = "embedded-io")]
+impl<W: embedded_io::Write> Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0.write_all(s.as_bytes()).map_err(|_| core::fmt::Error)
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432",
+        idents(
+            AsyncI2c(sync = "I2c"),
+            AsyncErrorType(sync = "ErrorType"),
+            AsyncDelaySource(sync = "DelaySource")
+        )
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T> AsyncDS4432<I, T> {
+    /// Read both outputs and log them, timestamped with the given clock
+    pub async fn log_csv<W: Write, C: Clock>(
+        &mut self,
+        logger: &mut CsvLogger<W>,
+        clock: &C,
+    ) -> Result<(), LogError<I::Error>> {
+        trace!("log_csv");
+
+        let timestamp_us = clock.now_us();
+        let state = self.dump().await?;
+        let current_ua = PerOutput::new(Output::Zero, Output::One).map(|output| {
+            let code = register_to_signed(state.register(output));
+            self.scale(output).ok().map(|scale| {
+                let current = scale.current_ua(code.unsigned_abs() as u8);
+                if code < 0 {
+                    -current
+                } else {
+                    current
+                }
+            })
+        });
+        logger
+            .record(timestamp_us, &state, current_ua)
+            .map_err(|_| LogError::Write)
+    }
+
+    /// Log both outputs `count` times, waiting `period` between the samples
+    pub async fn log_csv_every<W: Write, C: Clock, D: AsyncDelaySource<T>>(
+        &mut self,
+        logger: &mut CsvLogger<W>,
+        clock: &C,
+        period: MicrosDurationU32,
+        count: u32,
+        delay: &mut D,
+    ) -> Result<(), LogError<I::Error>> {
+        trace!("log_csv_every");
+
+        for sample in 0..count {
+            if sample > 0 {
+                delay
+                    .provider(&mut self.delay)
+                    .delay_us(period.ticks())
+                    .await;
+            }
+            self.log_csv(logger, clock).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "sync", not(feature = "tiny")))]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal_mock::eh1::i2c;
+    use fugit::ExtU32;
+    use std::string::String;
+    use std::vec;
+
+    use crate::time::{MockClock, MockDelay};
+    use crate::SLAVE_ADDRESS;
+
+    #[test]
+    fn logs_periodic_samples() {
+        let expectations = [
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0x00]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::One as u8], vec![0x70]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0x81]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::One as u8], vec![0x70]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::with_rfs(mock, None, Some(80_000)).unwrap();
+        let mut logger = CsvLogger::new(String::new());
+        let clock = MockClock::new();
+
+        ds4432
+            .log_csv_every(
+                &mut logger,
+                &clock,
+                500.micros(),
+                2,
+                &mut MockDelay::new(&clock),
+            )
+            .unwrap();
+        assert_eq!(
+            logger.release(),
+            "timestamp_us,out0_code,out0_ua,out1_code,out1_ua\n\
+             0,0,,-112,-87.238\n\
+             500,1,,-112,-87.238\n"
+        );
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+}
@@ -0,0 +1,152 @@
+//! In-memory test double.
+//!
+//! [`MockBus`] emulates the DS4432 registers in plain memory, so board-support crates and
+//! application logic can be unit-tested at the driver-call level without writing I2C
+//! expectations. [`MockDs4432`] is the driver on top of it, with the same API as the real one.
+//! The registers follow the same model as the target-mode [`Emulator`].
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! use ds4432::mock::{MockBus, MockDs4432};
+//! use ds4432::{Output, Status};
+//!
+//! let mut ds4432 = MockDs4432::new(MockBus::new());
+//!
+//! ds4432.set_status(Output::One, Status::Sink(42)).unwrap();
+//! assert_eq!(ds4432.status(Output::One).unwrap(), Status::Sink(42));
+//! assert_eq!(ds4432.release().register(Output::One), 0x2A);
+//! ```
+
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+#[cfg(all(feature = "async", not(feature = "sync")))]
+use embedded_hal_async::i2c::{ErrorKind, ErrorType, NoAcknowledgeSource, Operation};
+
+use crate::emulator::Emulator;
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+#[cfg(feature = "sync")]
+use crate::DS4432;
+use crate::{Output, SLAVE_ADDRESS};
+
+/// The DS4432 driver on the in-memory bus.
+#[cfg(feature = "sync")]
+pub type MockDs4432 = DS4432<MockBus>;
+
+/// The async DS4432 driver on the in-memory bus.
+#[cfg(feature = "async")]
+pub type AsyncMockDs4432 = AsyncDS4432<MockBus>;
+
+/// An I2C bus holding a single DS4432 emulated in memory.
+#[derive(Debug, Clone)]
+pub struct MockBus {
+    device: Emulator,
+    failing: bool,
+}
+
+impl MockBus {
+    /// Create a bus with a DS4432 in its power-on state, both outputs disabled.
+    pub const fn new() -> Self {
+        Self {
+            device: Emulator::new(),
+            failing: false,
+        }
+    }
+
+    /// Get the register value of an output.
+    pub fn register(&self, output: Output) -> u8 {
+        self.device.register(output)
+    }
+
+    /// Change the register value of an output behind the driver's back.
+    pub fn set_register(&mut self, output: Output, value: u8) {
+        self.device.set_register(output, value);
+    }
+
+    /// Make every following transaction fail with a bus error, or succeed again.
+    pub fn set_failing(&mut self, failing: bool) {
+        self.failing = failing;
+    }
+
+    /// Execute a transaction on the emulated device.
+    fn process(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        if self.failing {
+            return Err(ErrorKind::Bus);
+        }
+        if address != SLAVE_ADDRESS {
+            return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+        }
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => {
+                    if !self.device.on_write(bytes) {
+                        return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data));
+                    }
+                }
+                Operation::Read(buf) => self.device.on_read(buf),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for MockBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ErrorType for MockBus {
+    type Error = ErrorKind;
+}
+
+#[cfg(feature = "sync")]
+impl I2c for MockBus {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.process(address, operations)
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncI2c for MockBus {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.process(address, operations)
+    }
+}
+
+#[cfg(all(test, feature = "sync", not(feature = "tiny")))]
+mod test {
+    use super::*;
+
+    use crate::{Error, Status};
+
+    #[test]
+    fn emulates_registers_and_failures() {
+        let mut ds4432 = MockDs4432::with_rfs(MockBus::new(), Some(80_000), None).unwrap();
+
+        ds4432
+            .set_status(Output::Zero, Status::SinkMicroAmp(88.0))
+            .unwrap();
+        ds4432.set_status(Output::One, Status::Source(42)).unwrap();
+        assert_eq!(ds4432.status(Output::One).unwrap(), Status::Source(42));
+        ds4432.disable(Output::Zero).unwrap();
+        ds4432.enable(Output::Zero).unwrap();
+
+        let (mut bus, config) = ds4432.into_parts();
+        assert_eq!(bus.register(Output::Zero), 0x70);
+        bus.set_failing(true);
+        let mut ds4432 = MockDs4432::from_parts(bus, config);
+        assert_eq!(ds4432.status(Output::One), Err(Error::I2c(ErrorKind::Bus)));
+    }
+}
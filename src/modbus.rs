@@ -0,0 +1,302 @@
+//! Modbus register-map bridge.
+//!
+//! Maps the state of the DAC onto Modbus registers so industrial controllers can drive it. The
+//! application keeps its Modbus RTU/TCP stack and forwards the register accesses of the
+//! "read holding registers", "write (multiple) registers" and "read input registers" functions
+//! to the driver. Values are 16 bits, signed values are two's complement.
+//!
+//! Holding registers (read/write):
+//!
+//! | Address | Content                                             |
+//! |---------|-----------------------------------------------------|
+//! | 0       | OUT0 signed code, negative codes sink               |
+//! | 1       | OUT1 signed code, negative codes sink               |
+//! | 2       | OUT0 signed current in 0.01µA, needs the OUT0 Rfs   |
+//! | 3       | OUT1 signed current in 0.01µA, needs the OUT1 Rfs   |
+//!
+//! Input registers (read only):
+//!
+//! | Address | Content                                             |
+//! |---------|-----------------------------------------------------|
+//! | 0, 1    | OUT0 Rfs in ohms, high word first, 0 if unknown     |
+//! | 2, 3    | OUT1 Rfs in ohms, high word first, 0 if unknown     |
+//! | 4       | enable bits, bit 0 for OUT0 and bit 1 for OUT1      |
+//! | 5       | maximum code allowed by the derating curve          |
+//! | 6       | OUT0 minimum current in 0.01µA, always 0            |
+//! | 7       | OUT0 derated full scale in 0.01µA, 0 if unknown     |
+//! | 8       | OUT1 minimum current in 0.01µA, always 0            |
+//! | 9       | OUT1 derated full scale in 0.01µA, 0 if unknown     |
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! # use embedded_hal_mock::eh1::i2c;
+//! use ds4432::DS4432;
+//!
+//! # let i2c = i2c::Mock::new(&[
+//! #     i2c::Transaction::write(0x48, vec![0xF9, 0x2A]),
+//! # ]);
+//! let mut ds4432 = DS4432::new(i2c);
+//!
+//! // sink code 42 on OUT1
+//! ds4432.modbus_write_holding(1, &[-42i16 as u16]).unwrap();
+//!
+//! let mut values = [0; 2];
+//! ds4432.modbus_read_input(5, &mut values).unwrap();
+//! assert_eq!(values, [127, 0]);
+//! # ds4432.release().done();
+//! ```
+
+#[cfg(feature = "sync")]
+use crate::DS4432;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::ErrorType;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+use crate::{register_to_signed, signed_to_register, Error, Output, Status};
+
+/// Number of holding registers.
+const HOLDING_REGISTERS: u16 = 4;
+/// Number of input registers.
+const INPUT_REGISTERS: u16 = 10;
+
+/// A Modbus exception code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[repr(u8)]
+pub enum Exception {
+    /// The register range is out of the map
+    IllegalDataAddress = 0x02,
+    /// The written value is invalid
+    IllegalDataValue = 0x03,
+    /// The device didn't respond
+    ServerDeviceFailure = 0x04,
+}
+
+impl<E> From<&Error<E>> for Exception {
+    fn from(error: &Error<E>) -> Self {
+        match error {
+            Error::I2c(_) | Error::Divergence { .. } | Error::Verify { .. } => {
+                Self::ServerDeviceFailure
+            }
+            _ => Self::IllegalDataValue,
+        }
+    }
+}
+
+/// Why a Modbus register access fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum ModbusError<E> {
+    /// The access is rejected by the register map
+    Exception(Exception),
+    /// The driver failed to execute the access
+    Device(Error<E>),
+}
+
+impl<E> From<Exception> for ModbusError<E> {
+    fn from(exception: Exception) -> Self {
+        Self::Exception(exception)
+    }
+}
+
+impl<E> From<Error<E>> for ModbusError<E> {
+    fn from(error: Error<E>) -> Self {
+        Self::Device(error)
+    }
+}
+
+impl<E> From<&ModbusError<E>> for Exception {
+    fn from(error: &ModbusError<E>) -> Self {
+        match error {
+            ModbusError::Exception(exception) => *exception,
+            ModbusError::Device(error) => error.into(),
+        }
+    }
+}
+
+/// Check that a register range is in a map of `count` registers.
+fn check_range(address: u16, len: usize, count: u16) -> core::result::Result<(), Exception> {
+    if len == 0 || address as usize + len > count as usize {
+        return Err(Exception::IllegalDataAddress);
+    }
+    Ok(())
+}
+
+fn output(index: u16) -> Output {
+    Output::ALL[index as usize % 2]
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432",
+        idents(AsyncI2c(sync = "I2c"), AsyncErrorType(sync = "ErrorType"))
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T> AsyncDS4432<I, T> {
+    /// Read holding registers starting at `address`, filling `values`
+    pub async fn modbus_read_holding(
+        &mut self,
+        address: u16,
+        values: &mut [u16],
+    ) -> core::result::Result<(), ModbusError<I::Error>> {
+        trace!("modbus_read_holding");
+
+        check_range(address, values.len(), HOLDING_REGISTERS)?;
+        for (register, value) in (address..).zip(values.iter_mut()) {
+            let output = output(register);
+            let code = register_to_signed(self.read_register(output).await?);
+            *value = if register < 2 {
+                code as u16
+            } else {
+                let scale = self.scale(output)?;
+                let current = scale.current_ua(code.unsigned_abs() as u8) * 100.0;
+                (if code < 0 { -current } else { current }) as i16 as u16
+            };
+        }
+        Ok(())
+    }
+
+    /// Write holding registers starting at `address`, applying each value to the device
+    pub async fn modbus_write_holding(
+        &mut self,
+        address: u16,
+        values: &[u16],
+    ) -> core::result::Result<(), ModbusError<I::Error>> {
+        trace!("modbus_write_holding");
+
+        check_range(address, values.len(), HOLDING_REGISTERS)?;
+        for (register, value) in (address..).zip(values.iter()) {
+            let output = output(register);
+            let value = *value as i16;
+            if register < 2 {
+                if !(-127..=127).contains(&value) {
+                    return Err(Exception::IllegalDataValue.into());
+                }
+                self.write_register(output, signed_to_register(value))
+                    .await?;
+            } else {
+                let current = value as f32 / 100.0;
+                let status = if value > 0 {
+                    Status::SourceMicroAmp(current)
+                } else if value < 0 {
+                    Status::SinkMicroAmp(-current)
+                } else {
+                    Status::Disable
+                };
+                self.set_status(output, status).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read input registers starting at `address`, filling `values`
+    ///
+    /// The enable bits are read from the device, the other registers come from the driver
+    /// configuration.
+    pub async fn modbus_read_input(
+        &mut self,
+        address: u16,
+        values: &mut [u16],
+    ) -> core::result::Result<(), ModbusError<I::Error>> {
+        trace!("modbus_read_input");
+
+        check_range(address, values.len(), INPUT_REGISTERS)?;
+        for (register, value) in (address..).zip(values.iter_mut()) {
+            *value = match register {
+                0..=3 => {
+                    let rfs = self.config.rfs_ohm[output(register / 2)].unwrap_or(0);
+                    if register % 2 == 0 {
+                        (rfs >> 16) as u16
+                    } else {
+                        rfs as u16
+                    }
+                }
+                4 => {
+                    let mut bits = 0;
+                    for output in Output::ALL {
+                        if register_to_signed(self.read_register(output).await?) != 0 {
+                            bits |= 1 << output.index();
+                        }
+                    }
+                    bits
+                }
+                5 => self.config.max_code() as u16,
+                // any code down to 0 is valid
+                6 | 8 => 0,
+                _ => {
+                    let max_code = self.config.max_code();
+                    self.scale(output((register - 6) / 2))
+                        .map_or(0, |scale| (scale.current_ua(max_code) * 100.0) as u16)
+                }
+            };
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal_mock::eh1::i2c;
+    use std::vec;
+
+    use crate::derating::DeratingCurve;
+    use crate::SLAVE_ADDRESS;
+
+    #[test]
+    fn maps_currents_and_configuration() {
+        let expectations = [
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0xF0]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0xF0]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0xF0]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::One as u8], vec![0x80]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0x20]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::One as u8], vec![0x00]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::with_rfs(mock, Some(80_000), None).unwrap();
+
+        ds4432.modbus_write_holding(2, &[8800]).unwrap();
+        let mut values = [0; 1];
+        ds4432.modbus_read_holding(2, &mut values).unwrap();
+        assert_eq!(values, [8723]);
+
+        let mut values = [0; 5];
+        ds4432.modbus_read_input(0, &mut values).unwrap();
+        assert_eq!(values, [1, 14_464, 0, 0, 0b01]);
+
+        ds4432.set_derating(Some(DeratingCurve::new(&[(25, 127), (85, 64)])));
+        ds4432.set_temperature(85.0).unwrap();
+        ds4432.modbus_read_input(5, &mut values).unwrap();
+        assert_eq!(values, [64, 0, 4_985, 0, 0]);
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[test]
+    fn rejects_out_of_map_ranges() {
+        let mock = i2c::Mock::new(&[]);
+        let mut ds4432 = DS4432::new(mock);
+
+        let error = ds4432.modbus_write_holding(3, &[0, 0]).unwrap_err();
+        assert_eq!(Exception::from(&error), Exception::IllegalDataAddress);
+        let error = ds4432.modbus_write_holding(0, &[200]).unwrap_err();
+        assert_eq!(Exception::from(&error), Exception::IllegalDataValue);
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+}
@@ -0,0 +1,232 @@
+//! Bus pacing for shared I2C buses.
+//!
+//! High-rate DAC updates can starve the other devices of a shared I2C bus. Wrapping the bus
+//! given to the driver in a [`PacedI2c`] keeps a minimum gap between two transactions so other
+//! masters, or other tasks sharing the bus, get a chance to use it. The end of the last
+//! transaction is taken from a [`Clock`], so only the part of the gap not already spent
+//! elsewhere is waited.
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c};
+//! use ds4432::pacing::PacedI2c;
+//! use ds4432::time::MockClock;
+//! use ds4432::DS4432;
+//! use fugit::ExtU32;
+//!
+//! # let i2c = i2c::Mock::new(&[]);
+//! # let delay = NoopDelay;
+//! let clock = MockClock::new();
+//! // leave at least 200µs between two transactions
+//! let mut ds4432 = DS4432::new(PacedI2c::new(i2c, delay, &clock, 200.micros()));
+//! # ds4432.release().release().0.done();
+//! ```
+
+use fugit::MicrosDurationU32;
+
+#[cfg(feature = "sync")]
+use embedded_hal::delay::DelayNs;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::{ErrorType, I2c, Operation};
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs as AsyncDelayNs;
+#[cfg(all(feature = "async", not(feature = "sync")))]
+use embedded_hal_async::i2c::ErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::{I2c as AsyncI2c, Operation as AsyncOperation};
+
+use crate::time::Clock;
+
+/// An I2C bus `I` keeping a minimum gap between transactions, using the delay provider `D` and
+/// the clock `C`.
+#[derive(Debug)]
+pub struct PacedI2c<I, D, C> {
+    i2c: I,
+    delay: D,
+    clock: C,
+    gap: MicrosDurationU32,
+    /// End of the last transaction, in microseconds
+    last_us: Option<u64>,
+}
+
+impl<I, D, C: Clock> PacedI2c<I, D, C> {
+    /// Wrap an I2C bus, leaving at least `gap` between two transactions.
+    pub fn new(i2c: I, delay: D, clock: C, gap: MicrosDurationU32) -> Self {
+        Self {
+            i2c,
+            delay,
+            clock,
+            gap,
+            last_us: None,
+        }
+    }
+
+    /// Change the gap left between two transactions.
+    pub fn set_gap(&mut self, gap: MicrosDurationU32) {
+        self.gap = gap;
+    }
+
+    /// Return the underlying I2C bus, delay provider and clock.
+    pub fn release(self) -> (I, D, C) {
+        (self.i2c, self.delay, self.clock)
+    }
+
+    /// Get the part of the gap still to wait before the next transaction.
+    fn remaining_us(&self) -> u32 {
+        match self.last_us {
+            Some(last_us) => {
+                let elapsed = self.clock.now_us().saturating_sub(last_us);
+                (self.gap.ticks() as u64).saturating_sub(elapsed) as u32
+            }
+            None => 0,
+        }
+    }
+
+    /// Record the end of a transaction.
+    fn done(&mut self) {
+        self.last_us = Some(self.clock.now_us());
+    }
+}
+
+#[cfg(feature = "sync")]
+impl<I, D: DelayNs, C: Clock> PacedI2c<I, D, C> {
+    /// Wait for the end of the gap since the last transaction.
+    fn pace(&mut self) {
+        let remaining = self.remaining_us();
+        if remaining > 0 {
+            self.delay.delay_us(remaining);
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<I, D: AsyncDelayNs, C: Clock> PacedI2c<I, D, C> {
+    /// Wait for the end of the gap since the last transaction.
+    async fn pace_async(&mut self) {
+        let remaining = self.remaining_us();
+        if remaining > 0 {
+            self.delay.delay_us(remaining).await;
+        }
+    }
+}
+
+impl<I: ErrorType, D, C> ErrorType for PacedI2c<I, D, C> {
+    type Error = I::Error;
+}
+
+#[cfg(feature = "sync")]
+impl<I: I2c, D: DelayNs, C: Clock> I2c for PacedI2c<I, D, C> {
+    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.pace();
+        let result = self.i2c.read(address, read);
+        self.done();
+        result
+    }
+
+    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        self.pace();
+        let result = self.i2c.write(address, write);
+        self.done();
+        result
+    }
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.pace();
+        let result = self.i2c.write_read(address, write, read);
+        self.done();
+        result
+    }
+
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.pace();
+        let result = self.i2c.transaction(address, operations);
+        self.done();
+        result
+    }
+}
+
+#[cfg(feature = "async")]
+impl<I: AsyncI2c, D: AsyncDelayNs, C: Clock> AsyncI2c for PacedI2c<I, D, C> {
+    async fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.pace_async().await;
+        let result = self.i2c.read(address, read).await;
+        self.done();
+        result
+    }
+
+    async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        self.pace_async().await;
+        let result = self.i2c.write(address, write).await;
+        self.done();
+        result
+    }
+
+    async fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.pace_async().await;
+        let result = self.i2c.write_read(address, write, read).await;
+        self.done();
+        result
+    }
+
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [AsyncOperation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.pace_async().await;
+        let result = self.i2c.transaction(address, operations).await;
+        self.done();
+        result
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal_mock::eh1::i2c;
+    use fugit::{ExtU32, NanosDurationU64};
+    use std::vec;
+
+    use crate::time::{MockClock, MockDelay};
+    use crate::{Output, Status, DS4432, SLAVE_ADDRESS};
+
+    #[test]
+    fn only_waits_the_rest_of_the_gap() {
+        let i2c = i2c::Mock::new(&[
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x2A]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0x2A]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x00]),
+        ]);
+        let clock = MockClock::new();
+        let delay = MockDelay::new(&clock);
+        let mut ds4432 = DS4432::new(PacedI2c::new(i2c, delay, &clock, 200.micros()));
+
+        ds4432.set_status(Output::Zero, Status::Sink(42)).unwrap();
+        assert_eq!(clock.now_us(), 0);
+        assert_eq!(ds4432.status(Output::Zero).unwrap(), Status::Sink(42));
+        assert_eq!(clock.now_us(), 200);
+        clock.advance(NanosDurationU64::micros(150));
+        ds4432.set_status(Output::Zero, Status::Disable).unwrap();
+        assert_eq!(clock.now_us(), 400);
+
+        let (mut i2c, _, _) = ds4432.release().release();
+        i2c.done();
+    }
+}
@@ -0,0 +1,102 @@
+use core::ops::{Index, IndexMut};
+
+use crate::Output;
+
+/// A value for each output of the DS4432.
+///
+/// # Example
+/// ```
+/// use ds4432::{Output, PerOutput};
+///
+/// let mut rfs = PerOutput::new(80_000, 120_000);
+/// rfs[Output::One] = 40_000;
+///
+/// assert_eq!(rfs[Output::Zero], 80_000);
+/// assert_eq!(rfs[1], 40_000);
+/// assert_eq!(rfs.map(|r| r / 1000), PerOutput::new(80, 40));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct PerOutput<T> {
+    values: [T; 2],
+}
+
+impl<T> PerOutput<T> {
+    /// Create a container from the values of each output.
+    pub const fn new(zero: T, one: T) -> Self {
+        Self {
+            values: [zero, one],
+        }
+    }
+
+    /// Get the value of an output.
+    pub fn get(&self, output: Output) -> &T {
+        &self.values[output.index()]
+    }
+
+    /// Get a mutable reference to the value of an output.
+    pub fn get_mut(&mut self, output: Output) -> &mut T {
+        &mut self.values[output.index()]
+    }
+
+    /// Iterate over the outputs and their values.
+    pub fn iter(&self) -> impl Iterator<Item = (Output, &T)> {
+        Output::ALL.into_iter().zip(self.values.iter())
+    }
+
+    /// Apply a function to the value of each output.
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> PerOutput<U> {
+        PerOutput {
+            values: self.values.map(f),
+        }
+    }
+}
+
+impl<T: Clone> PerOutput<T> {
+    /// Create a container with the same value for each output.
+    pub fn splat(value: T) -> Self {
+        Self::new(value.clone(), value)
+    }
+}
+
+impl<T> From<(T, T)> for PerOutput<T> {
+    fn from((zero, one): (T, T)) -> Self {
+        Self::new(zero, one)
+    }
+}
+
+impl<T> From<PerOutput<T>> for (T, T) {
+    fn from(value: PerOutput<T>) -> Self {
+        let [zero, one] = value.values;
+        (zero, one)
+    }
+}
+
+impl<T> Index<Output> for PerOutput<T> {
+    type Output = T;
+
+    fn index(&self, output: Output) -> &T {
+        self.get(output)
+    }
+}
+
+impl<T> IndexMut<Output> for PerOutput<T> {
+    fn index_mut(&mut self, output: Output) -> &mut T {
+        self.get_mut(output)
+    }
+}
+
+impl<T> Index<usize> for PerOutput<T> {
+    type Output = T;
+
+    /// Index by channel number, panicking if it is out of range.
+    fn index(&self, index: usize) -> &T {
+        &self.values[index]
+    }
+}
+
+impl<T> IndexMut<usize> for PerOutput<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.values[index]
+    }
+}
@@ -0,0 +1,218 @@
+//! Pulsed excitation.
+//!
+//! A [`Pulse`] sources or sinks a current for `on`, then disables the output for `off`, e.g. to
+//! excite a sensor bridge only while it is sampled. `pulse` repeats it a number of times and
+//! `pulse_while` until a condition is no longer met, the output being left disabled.
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c};
+//! use ds4432::pulse::Pulse;
+//! use ds4432::{Output, Status, DS4432};
+//! use fugit::ExtU32;
+//!
+//! # let i2c = i2c::Mock::new(&[
+//! #     i2c::Transaction::write(0x48, vec![0xF9, 0x90]),
+//! #     i2c::Transaction::write(0x48, vec![0xF9, 0x00]),
+//! #     i2c::Transaction::write(0x48, vec![0xF9, 0x90]),
+//! #     i2c::Transaction::write(0x48, vec![0xF9, 0x00]),
+//! # ]);
+//! let mut ds4432 = DS4432::new(i2c);
+//! let pulse = Pulse::new(Status::Source(16), 2.millis(), 8.millis());
+//!
+//! ds4432.pulse(Output::One, &pulse, 2, &mut NoopDelay).unwrap();
+//! # ds4432.release().done();
+//! ```
+
+#[cfg(feature = "async")]
+use crate::delay::AsyncDelaySource;
+#[cfg(feature = "sync")]
+use crate::delay::DelaySource;
+#[cfg(feature = "sync")]
+use crate::DS4432;
+#[cfg(feature = "sync")]
+use embedded_hal::delay::DelayNs;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::ErrorType;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs as AsyncDelayNs;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+use fugit::MicrosDurationU32;
+
+use crate::delay::DriverDelay;
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+use crate::{Output, Result, Status};
+
+/// A current applied for `on`, followed by the output disabled for `off`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Pulse {
+    pub status: Status,
+    pub on: MicrosDurationU32,
+    pub off: MicrosDurationU32,
+}
+
+impl Pulse {
+    /// Create a pulse applying `status` for `on`, then disabling the output for `off`.
+    pub const fn new(status: Status, on: MicrosDurationU32, off: MicrosDurationU32) -> Self {
+        Self { status, on, off }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432",
+        idents(
+            AsyncI2c(sync = "I2c"),
+            AsyncErrorType(sync = "ErrorType"),
+            AsyncDelaySource(sync = "DelaySource")
+        )
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T> AsyncDS4432<I, T> {
+    /// Emit `count` pulses on an output
+    pub async fn pulse<D: AsyncDelaySource<T>>(
+        &mut self,
+        output: Output,
+        pulse: &Pulse,
+        count: u32,
+        delay: &mut D,
+    ) -> Result<(), I::Error> {
+        trace!("pulse");
+
+        if count == 0 {
+            return Ok(());
+        }
+        let mut remaining = count;
+        self.pulse_while(output, pulse, delay, || {
+            remaining = remaining.saturating_sub(1);
+            remaining > 0
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Emit pulses on an output as long as `keep_going` returns true, checked after each pulse,
+    /// and return the number of pulses emitted
+    pub async fn pulse_while<D: AsyncDelaySource<T>, F: FnMut() -> bool>(
+        &mut self,
+        output: Output,
+        pulse: &Pulse,
+        delay: &mut D,
+        mut keep_going: F,
+    ) -> Result<u32, I::Error> {
+        trace!("pulse_while");
+
+        let value = self.encode(output, pulse.status)?;
+        let mut count = 0;
+        loop {
+            self.write_register(output, value).await?;
+            delay
+                .provider(&mut self.delay)
+                .delay_us(pulse.on.ticks())
+                .await;
+            self.write_register(output, 0x00).await?;
+            delay
+                .provider(&mut self.delay)
+                .delay_us(pulse.off.ticks())
+                .await;
+            count += 1;
+            if !keep_going() {
+                return Ok(count);
+            }
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432",
+        idents(
+            AsyncI2c(sync = "I2c"),
+            AsyncErrorType(sync = "ErrorType"),
+            AsyncDelayNs(sync = "DelayNs")
+        )
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T: AsyncDelayNs> AsyncDS4432<I, T> {
+    /// `pulse` using the driver's delay
+    pub async fn pulses(
+        &mut self,
+        output: Output,
+        pulse: &Pulse,
+        count: u32,
+    ) -> Result<(), I::Error> {
+        trace!("pulses");
+
+        self.pulse(output, pulse, count, &mut DriverDelay).await
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal_mock::eh1::i2c;
+    use fugit::ExtU32;
+    use std::vec;
+
+    use crate::time::{Clock, MockClock, MockDelay};
+    use crate::SLAVE_ADDRESS;
+
+    #[test]
+    fn pulses_until_stopped() {
+        let expectations = [
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x05]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x00]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x05]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x00]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x05]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x00]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::new(mock);
+        let clock = MockClock::new();
+        let pulse = Pulse::new(Status::Sink(5), 10.micros(), 40.micros());
+
+        let count = ds4432
+            .pulse_while(Output::Zero, &pulse, &mut MockDelay::new(&clock), || {
+                clock.now_us() < 150
+            })
+            .unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(clock.now_us(), 150);
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[test]
+    fn pulses_with_the_owned_delay() {
+        let expectations = [
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x83]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x00]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let clock = MockClock::new();
+        let mut ds4432 = DS4432::new(mock).with_delay(MockDelay::new(&clock));
+        let pulse = Pulse::new(Status::Source(3), 20.micros(), 30.micros());
+
+        ds4432.pulses(Output::One, &pulse, 1).unwrap();
+        assert_eq!(clock.now_us(), 50);
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+}
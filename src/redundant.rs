@@ -0,0 +1,196 @@
+//! Redundancy wrapper driving two DS4432s with the same setpoints.
+//!
+//! Every write is applied to both devices and every readback is cross-checked, any difference
+//! between the two register values is reported as an [`Error::Divergence`]. A write the
+//! secondary device fails is rolled back on the primary one, so both keep the same value.
+
+#[cfg(feature = "sync")]
+use crate::DS4432;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::ErrorType;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+use crate::delay::NoDelay;
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+use crate::{Error, Output, Result, Status};
+
+/// Two DS4432 Digital To Analog (DAC) converters in a voting arrangement.
+///
+/// As the DS4432 has a fixed I2C address, both devices sit on their own I2C bus `I`. The
+/// drivers may own a delay provider of type `T`.
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "RedundantDS4432",
+        idents(AsyncDS4432(sync = "DS4432"))
+    ),
+    async(feature = "async", keep_self)
+)]
+pub struct AsyncRedundantDS4432<I, T = NoDelay> {
+    primary: AsyncDS4432<I, T>,
+    secondary: AsyncDS4432<I, T>,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "RedundantDS4432",
+        idents(
+            AsyncDS4432(sync = "DS4432"),
+            AsyncI2c(sync = "I2c"),
+            AsyncErrorType(sync = "ErrorType")
+        )
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T> AsyncRedundantDS4432<I, T> {
+    /// Create a new redundant pair from two drivers.
+    ///
+    /// Both drivers are expected to be configured with the same Rfs values, the primary one is
+    /// used for the microamps convertions.
+    pub fn new(primary: AsyncDS4432<I, T>, secondary: AsyncDS4432<I, T>) -> Self {
+        trace!("new");
+        Self { primary, secondary }
+    }
+
+    /// Set the current sink/source status and code of an output on both devices and check that
+    /// they read back the same value, returning the status they hold
+    ///
+    /// If the secondary device fails the write, the primary one is set back to its previous
+    /// value before the error is returned.
+    pub async fn set_status(&mut self, output: Output, status: Status) -> Result<Status, I::Error> {
+        trace!("set_status");
+
+        let value = self.primary.encode(output, status)?;
+        let previous = self.primary.read_register(output).await?;
+        self.primary.write_register(output, value).await?;
+        if let Err(error) = self.secondary.write_register(output, value).await {
+            self.primary.write_register(output, previous).await?;
+            return Err(error);
+        }
+        let value = self.read_register(output).await?;
+        Ok(self.primary.decode(output, value))
+    }
+
+    /// Get the current sink/source status and code of an output, if both devices agree
+    pub async fn status(&mut self, output: Output) -> Result<Status, I::Error> {
+        trace!("status");
+
+        let value = self.read_register(output).await?;
+        Ok(self.primary.decode(output, value))
+    }
+
+    /// Read the register value of an output from both devices and cross-check them
+    async fn read_register(&mut self, output: Output) -> Result<u8, I::Error> {
+        let primary = self.primary.read_register(output).await?;
+        let secondary = self.secondary.read_register(output).await?;
+        if primary != secondary {
+            if self.primary.config.verbosity[output].warns() {
+                error!("{:?} diverged: {:x} != {:x}", output, primary, secondary);
+            }
+            return Err(Error::Divergence {
+                output,
+                primary,
+                secondary,
+            });
+        }
+        Ok(primary)
+    }
+
+    /// Return the underlying drivers, primary first
+    pub fn release(self) -> (AsyncDS4432<I, T>, AsyncDS4432<I, T>) {
+        (self.primary, self.secondary)
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal::i2c::ErrorKind;
+    use embedded_hal_mock::eh1::i2c;
+    use std::vec;
+
+    use crate::SLAVE_ADDRESS;
+
+    #[test]
+    fn set_status_writes_both_devices() {
+        let primary = i2c::Mock::new(&[
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0x00]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0xAA]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0xAA]),
+        ]);
+        let secondary = i2c::Mock::new(&[
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0xAA]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0xAA]),
+        ]);
+        let mut redundant = RedundantDS4432::new(DS4432::new(primary), DS4432::new(secondary));
+
+        redundant
+            .set_status(Output::Zero, Status::Source(42))
+            .unwrap();
+
+        let (primary, secondary) = redundant.release();
+        primary.release().done();
+        secondary.release().done();
+    }
+
+    #[test]
+    fn set_status_rolls_back_the_primary() {
+        let primary = i2c::Mock::new(&[
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::One as u8], vec![0x05]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x2A]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x05]),
+        ]);
+        let secondary = i2c::Mock::new(&[i2c::Transaction::write(
+            SLAVE_ADDRESS,
+            vec![Output::One as u8, 0x2A],
+        )
+        .with_error(ErrorKind::Other)]);
+        let mut redundant = RedundantDS4432::new(DS4432::new(primary), DS4432::new(secondary));
+
+        assert_eq!(
+            redundant.set_status(Output::One, Status::Sink(42)),
+            Err(Error::I2c(ErrorKind::Other))
+        );
+
+        let (primary, secondary) = redundant.release();
+        primary.release().done();
+        secondary.release().done();
+    }
+
+    #[test]
+    fn status_reports_divergence() {
+        let primary = i2c::Mock::new(&[i2c::Transaction::write_read(
+            SLAVE_ADDRESS,
+            vec![Output::One as u8],
+            vec![0x2A],
+        )]);
+        let secondary = i2c::Mock::new(&[i2c::Transaction::write_read(
+            SLAVE_ADDRESS,
+            vec![Output::One as u8],
+            vec![0x2B],
+        )]);
+        let mut redundant = RedundantDS4432::new(DS4432::new(primary), DS4432::new(secondary));
+
+        assert_eq!(
+            redundant.status(Output::One),
+            Err(Error::Divergence {
+                output: Output::One,
+                primary: 0x2A,
+                secondary: 0x2B
+            })
+        );
+
+        let (primary, secondary) = redundant.release();
+        primary.release().done();
+        secondary.release().done();
+    }
+}
@@ -0,0 +1,342 @@
+//! SCPI-style command handler.
+//!
+//! Exposes the DAC as a lab-instrument-style endpoint: the application reads command lines
+//! from its transport (UART, TCP, ...) and hands them to `handle_scpi`, which writes the query
+//! responses into any [`core::fmt::Write`]. Mnemonics accept their short and long forms in any
+//! case, the output number is given as a suffix:
+//!
+//! - `*IDN?` identifies the device.
+//! - `SOURce<n>:CURRent <current>[UA]` sets the current of an output in microamps, negative
+//!   currents sink, `SOURce<n>:CURRent?` reads it back. Both need the Rfs of the output.
+//! - `SOURce<n>:CODE <code>` sets the raw signed code of an output, negative codes sink,
+//!   `SOURce<n>:CODE?` reads it back.
+//! - `OUTPut<n>:STATe OFF` disables an output and `OUTPut<n>:STATe ON` restores the status it
+//!   had before, `OUTPut<n>:STATe?` reads `1` if the output is enabled and `0` otherwise.
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! # use embedded_hal_mock::eh1::i2c;
+//! use ds4432::DS4432;
+//!
+//! # let i2c = i2c::Mock::new(&[
+//! #     i2c::Transaction::write(0x48, vec![0xF8, 0xAA]),
+//! #     i2c::Transaction::write_read(0x48, vec![0xF9], vec![0x00]),
+//! # ]);
+//! let mut ds4432 = DS4432::new(i2c);
+//! let mut response = String::new();
+//!
+//! ds4432.handle_scpi("SOUR0:CODE 42", &mut response).unwrap();
+//! ds4432.handle_scpi("outp1:stat?", &mut response).unwrap();
+//! assert_eq!(response, "0\n");
+//! # ds4432.release().done();
+//! ```
+
+use core::fmt::Write;
+
+#[cfg(feature = "sync")]
+use crate::DS4432;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::ErrorType;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+use crate::{register_to_signed, signed_to_register, Error, Output, Status};
+
+/// Why a SCPI command can't be executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum CommandError {
+    /// The command header is unknown (SCPI error -113)
+    UndefinedHeader,
+    /// The parameter is missing (SCPI error -109)
+    MissingParameter,
+    /// The parameter can't be parsed or is out of range (SCPI error -224)
+    IllegalParameter,
+    /// The response can't be written
+    Response,
+}
+
+impl CommandError {
+    /// The standard SCPI error code.
+    pub fn code(&self) -> i16 {
+        match self {
+            Self::UndefinedHeader => -113,
+            Self::MissingParameter => -109,
+            Self::IllegalParameter => -224,
+            Self::Response => -300,
+        }
+    }
+}
+
+/// Why a SCPI command line fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum ScpiError<E> {
+    /// The command can't be executed
+    Command(CommandError),
+    /// The driver failed to execute the command
+    Device(Error<E>),
+}
+
+impl<E> From<CommandError> for ScpiError<E> {
+    fn from(error: CommandError) -> Self {
+        Self::Command(error)
+    }
+}
+
+impl<E> From<Error<E>> for ScpiError<E> {
+    fn from(error: Error<E>) -> Self {
+        Self::Device(error)
+    }
+}
+
+/// A parsed command header.
+enum Header {
+    Identify,
+    Current(Output),
+    Code(Output),
+    State(Output),
+}
+
+/// Check a mnemonic against its long form, whose uppercase prefix is the short form.
+fn mnemonic(token: &str, long: &str) -> bool {
+    let short = long.bytes().take_while(u8::is_ascii_uppercase).count();
+    token.eq_ignore_ascii_case(long) || token.eq_ignore_ascii_case(&long[..short])
+}
+
+/// Split a mnemonic and its output suffix.
+fn suffixed(token: &str, long: &str) -> Option<Output> {
+    let split = token.find(|c: char| c.is_ascii_digit())?;
+    if !mnemonic(&token[..split], long) {
+        return None;
+    }
+    match &token[split..] {
+        "0" => Some(Output::Zero),
+        "1" => Some(Output::One),
+        _ => None,
+    }
+}
+
+fn parse_header(header: &str) -> core::result::Result<Header, CommandError> {
+    if header.eq_ignore_ascii_case("*IDN") {
+        return Ok(Header::Identify);
+    }
+    let header = header.strip_prefix(':').unwrap_or(header);
+    let (node, leaf) = header
+        .split_once(':')
+        .ok_or(CommandError::UndefinedHeader)?;
+
+    if let Some(output) = suffixed(node, "SOURce") {
+        if mnemonic(leaf, "CURRent") {
+            return Ok(Header::Current(output));
+        } else if mnemonic(leaf, "CODE") {
+            return Ok(Header::Code(output));
+        }
+    } else if let Some(output) = suffixed(node, "OUTPut") {
+        if mnemonic(leaf, "STATe") {
+            return Ok(Header::State(output));
+        }
+    }
+    Err(CommandError::UndefinedHeader)
+}
+
+/// Parse a signed current with an optional `UA` unit.
+fn parse_current(parameter: &str) -> Option<f32> {
+    let len = parameter.len();
+    let value = match parameter.get(len.saturating_sub(2)..) {
+        Some(unit) if unit.eq_ignore_ascii_case("ua") => &parameter[..len - 2],
+        _ => parameter,
+    };
+    // `f32::from_str` also accepts "NaN" and "inf"
+    value
+        .parse()
+        .ok()
+        .filter(|current: &f32| current.is_finite())
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432",
+        idents(AsyncI2c(sync = "I2c"), AsyncErrorType(sync = "ErrorType"))
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T> AsyncDS4432<I, T> {
+    /// Execute a SCPI command line, writing the response of queries into `response`
+    pub async fn handle_scpi<W: Write>(
+        &mut self,
+        command: &str,
+        response: &mut W,
+    ) -> core::result::Result<(), ScpiError<I::Error>> {
+        trace!("handle_scpi");
+
+        let command = command.trim();
+        let (header, parameter) = match command.split_once(char::is_whitespace) {
+            Some((header, parameter)) => (header, Some(parameter.trim())),
+            None => (command, None),
+        };
+        let (header, query) = match header.strip_suffix('?') {
+            Some(header) => (header, true),
+            None => (header, false),
+        };
+        let header = parse_header(header)?;
+
+        if query {
+            if parameter.is_some() {
+                return Err(CommandError::IllegalParameter.into());
+            }
+            match header {
+                Header::Identify => {
+                    writeln!(response, "Maxim,DS4432,0,{}", env!("CARGO_PKG_VERSION"))
+                }
+                Header::Current(output) => {
+                    let scale = self.scale(output)?;
+                    let code = register_to_signed(self.read_register(output).await?);
+                    let current = scale.current_ua(code.unsigned_abs() as u8);
+                    writeln!(response, "{:.3}", if code < 0 { -current } else { current })
+                }
+                Header::Code(output) => {
+                    let code = register_to_signed(self.read_register(output).await?);
+                    writeln!(response, "{}", code)
+                }
+                Header::State(output) => {
+                    let code = register_to_signed(self.read_register(output).await?);
+                    writeln!(response, "{}", if code == 0 { 0 } else { 1 })
+                }
+            }
+            .map_err(|_| CommandError::Response.into())
+        } else {
+            let parameter = parameter.ok_or(CommandError::MissingParameter)?;
+            let illegal = || ScpiError::Command(CommandError::IllegalParameter);
+            match header {
+                Header::Identify => Err(CommandError::UndefinedHeader.into()),
+                Header::Current(output) => {
+                    let current = parse_current(parameter).ok_or_else(illegal)?;
+                    let status = if current > 0.0 {
+                        Status::SourceMicroAmp(current)
+                    } else if current < 0.0 {
+                        Status::SinkMicroAmp(-current)
+                    } else {
+                        Status::Disable
+                    };
+                    self.set_status(output, status).await?;
+                    Ok(())
+                }
+                Header::Code(output) => {
+                    let code: i16 = parameter.parse().map_err(|_| illegal())?;
+                    if !(-127..=127).contains(&code) {
+                        return Err(illegal());
+                    }
+                    Ok(self
+                        .write_register(output, signed_to_register(code))
+                        .await?)
+                }
+                Header::State(output) => {
+                    if parameter.eq_ignore_ascii_case("OFF") || parameter == "0" {
+                        Ok(self.disable(output).await?)
+                    } else if parameter.eq_ignore_ascii_case("ON") || parameter == "1" {
+                        Ok(self.enable(output).await?)
+                    } else {
+                        Err(illegal())
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal_mock::eh1::i2c;
+    use std::string::String;
+    #[cfg(not(feature = "tiny"))]
+    use std::vec;
+
+    #[cfg(not(feature = "tiny"))]
+    use crate::SLAVE_ADDRESS;
+
+    #[cfg(not(feature = "tiny"))]
+    #[test]
+    fn sets_and_queries_current() {
+        let expectations = [
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x70]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::One as u8], vec![0x70]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::with_rfs(mock, None, Some(80_000)).unwrap();
+        let mut response = String::new();
+
+        ds4432
+            .handle_scpi("SOURCE1:CURR -88.0UA", &mut response)
+            .unwrap();
+        ds4432.handle_scpi(":sour1:curr?", &mut response).unwrap();
+        assert_eq!(response, "-87.238\n");
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[cfg(not(feature = "tiny"))]
+    #[test]
+    fn sets_current_below_the_smallest_full_scale() {
+        let expectations = [i2c::Transaction::write(
+            SLAVE_ADDRESS,
+            vec![Output::Zero as u8, 0xA9],
+        )];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::with_rfs(mock, Some(80_000), None).unwrap();
+        let mut response = String::new();
+
+        // source code 41
+        ds4432
+            .handle_scpi("SOUR0:CURR 32.5UA", &mut response)
+            .unwrap();
+        assert_eq!(response, "");
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[test]
+    fn rejects_invalid_commands() {
+        let mock = i2c::Mock::new(&[]);
+        let mut ds4432 = DS4432::new(mock);
+        let mut response = String::new();
+
+        let errors = [
+            ("SOUR2:CODE 1", CommandError::UndefinedHeader),
+            ("SOURC0:CODE 1", CommandError::UndefinedHeader),
+            ("SOUR0:CODE", CommandError::MissingParameter),
+            ("SOUR0:CODE 128", CommandError::IllegalParameter),
+            ("OUTP0:STAT 2", CommandError::IllegalParameter),
+            ("SOUR0:CURR NaN", CommandError::IllegalParameter),
+            ("SOUR0:CURR -infUA", CommandError::IllegalParameter),
+        ];
+        for (command, error) in errors {
+            assert_eq!(
+                ds4432.handle_scpi(command, &mut response),
+                Err(ScpiError::Command(error))
+            );
+        }
+        assert_eq!(
+            ds4432.handle_scpi("SOUR0:CURR 60", &mut response),
+            Err(ScpiError::Device(Error::UnknownRfs {
+                output: Output::Zero
+            }))
+        );
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+}
@@ -0,0 +1,547 @@
+//! Line-oriented stimulus scripts.
+//!
+//! A script describes a sequence of setpoints so a stimulus can be changed without recompiling
+//! the firmware. Each line holds one step, blank lines and lines starting with `#` are ignored:
+//!
+//! ```text
+//! # bias the sensor
+//! set out0 source 42
+//! wait 10ms
+//! ramp out1 to -100uA over 1s
+//! set out1 disable
+//! ```
+//!
+//! - `set <output> <value>` sets the status of an output.
+//! - `wait <duration>` waits for the given duration.
+//! - `ramp <output> to <value> over <duration>` ramps an output to the given value.
+//!
+//! Outputs are `out0` and `out1`. Values are `disable`, `source <code>`, `sink <code>`,
+//! `source <current>uA`, `sink <current>uA` or a signed `<current>uA` (negative currents sink).
+//! Durations are integers followed by `us`, `ms` or `s`.
+//!
+//! Sequences known at build time don't need a script: steps are `const`-constructible, so a
+//! stimulus table can be a `static` living in flash and be given to `play` as is.
+//!
+//! ```
+//! use ds4432::script::Step;
+//! use ds4432::{Output, Status};
+//! use fugit::MicrosDurationU32;
+//!
+//! static BIAS: [Step; 3] = [
+//!     Step::set(Output::Zero, Status::Source(42)),
+//!     Step::wait(MicrosDurationU32::millis(10)),
+//!     Step::ramp(Output::One, Status::Sink(100), MicrosDurationU32::secs(1)),
+//! ];
+//! ```
+//!
+//! # Example
+//! ```
+//! use ds4432::script::{Script, Step};
+//! use ds4432::{Output, Status};
+//! use fugit::MicrosDurationU32;
+//!
+//! let mut script = Script::new("set out0 source 42\n\n# done\nwait 10ms");
+//! assert_eq!(
+//!     script.next(),
+//!     Some(Ok(Step::Set {
+//!         output: Output::Zero,
+//!         status: Status::Source(42)
+//!     }))
+//! );
+//! assert_eq!(
+//!     script.next(),
+//!     Some(Ok(Step::Wait {
+//!         duration: MicrosDurationU32::millis(10)
+//!     }))
+//! );
+//! assert_eq!(script.next(), None);
+//! ```
+
+#[cfg(feature = "async")]
+use crate::delay::AsyncDelaySource;
+#[cfg(feature = "sync")]
+use crate::delay::DelaySource;
+#[cfg(feature = "sync")]
+use crate::DS4432;
+#[cfg(feature = "sync")]
+use embedded_hal::delay::DelayNs;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::ErrorType;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs as AsyncDelayNs;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+use crate::{Error, Output, Result, Status};
+use fugit::MicrosDurationU32;
+
+/// A step of a stimulus sequence.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum Step {
+    /// Set the status of an output
+    Set { output: Output, status: Status },
+    /// Wait for the given duration
+    Wait { duration: MicrosDurationU32 },
+    /// Ramp an output to the target status over the given duration
+    Ramp {
+        output: Output,
+        target: Status,
+        duration: MicrosDurationU32,
+    },
+}
+
+impl Step {
+    /// Create a step setting the status of an output.
+    pub const fn set(output: Output, status: Status) -> Self {
+        Step::Set { output, status }
+    }
+
+    /// Create a step waiting for the given duration.
+    pub const fn wait(duration: MicrosDurationU32) -> Self {
+        Step::Wait { duration }
+    }
+
+    /// Create a step ramping an output to the target status over the given duration.
+    pub const fn ramp(output: Output, target: Status, duration: MicrosDurationU32) -> Self {
+        Step::Ramp {
+            output,
+            target,
+            duration,
+        }
+    }
+}
+
+/// What is wrong with a script line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum ParseErrorKind {
+    /// The line doesn't start with a known command
+    UnknownCommand,
+    /// The output is not `out0` or `out1`
+    InvalidOutput,
+    /// The value can't be parsed
+    InvalidValue,
+    /// The duration can't be parsed
+    InvalidDuration,
+    /// A token is missing or unexpected
+    Syntax,
+}
+
+/// A script line that can't be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct ParseError {
+    /// The line number, starting at 1
+    pub line: usize,
+    /// What is wrong with the line
+    pub kind: ParseErrorKind,
+}
+
+/// Why a script can't be run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum ScriptError<E> {
+    /// A line of the script can't be parsed
+    Parse(ParseError),
+    /// The driver failed to execute a step
+    Device(Error<E>),
+}
+
+impl<E> From<ParseError> for ScriptError<E> {
+    fn from(error: ParseError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+impl<E> From<Error<E>> for ScriptError<E> {
+    fn from(error: Error<E>) -> Self {
+        Self::Device(error)
+    }
+}
+
+/// An iterator over the steps of a script.
+#[derive(Debug, Clone)]
+pub struct Script<'a> {
+    lines: core::iter::Enumerate<core::str::Lines<'a>>,
+}
+
+impl<'a> Script<'a> {
+    /// Create an iterator over the steps of the given script.
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            lines: text.lines().enumerate(),
+        }
+    }
+}
+
+impl Iterator for Script<'_> {
+    type Item = core::result::Result<Step, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, line) in self.lines.by_ref() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            return Some(parse_step(line).map_err(|kind| ParseError {
+                line: index + 1,
+                kind,
+            }));
+        }
+        None
+    }
+}
+
+/// Parse a single non-empty script line.
+pub(crate) fn parse_step(line: &str) -> core::result::Result<Step, ParseErrorKind> {
+    let mut tokens = line.split_whitespace();
+    let command = tokens.next().ok_or(ParseErrorKind::Syntax)?;
+
+    let step = if command.eq_ignore_ascii_case("set") {
+        let output = parse_output(tokens.next())?;
+        let status = parse_value(&mut tokens)?;
+        Step::Set { output, status }
+    } else if command.eq_ignore_ascii_case("wait") {
+        let duration = parse_duration(tokens.next())?;
+        Step::Wait { duration }
+    } else if command.eq_ignore_ascii_case("ramp") {
+        let output = parse_output(tokens.next())?;
+        expect_keyword(tokens.next(), "to")?;
+        let target = parse_value(&mut tokens)?;
+        expect_keyword(tokens.next(), "over")?;
+        let duration = parse_duration(tokens.next())?;
+        Step::Ramp {
+            output,
+            target,
+            duration,
+        }
+    } else {
+        return Err(ParseErrorKind::UnknownCommand);
+    };
+
+    match tokens.next() {
+        Some(_) => Err(ParseErrorKind::Syntax),
+        None => Ok(step),
+    }
+}
+
+pub(crate) fn expect_keyword(
+    token: Option<&str>,
+    keyword: &str,
+) -> core::result::Result<(), ParseErrorKind> {
+    match token {
+        Some(token) if token.eq_ignore_ascii_case(keyword) => Ok(()),
+        _ => Err(ParseErrorKind::Syntax),
+    }
+}
+
+pub(crate) fn parse_output(token: Option<&str>) -> core::result::Result<Output, ParseErrorKind> {
+    match token {
+        Some(token) if token.eq_ignore_ascii_case("out0") => Ok(Output::Zero),
+        Some(token) if token.eq_ignore_ascii_case("out1") => Ok(Output::One),
+        _ => Err(ParseErrorKind::InvalidOutput),
+    }
+}
+
+/// Parse a value, consuming one or two tokens.
+pub(crate) fn parse_value<'a>(
+    tokens: &mut impl Iterator<Item = &'a str>,
+) -> core::result::Result<Status, ParseErrorKind> {
+    let token = tokens.next().ok_or(ParseErrorKind::Syntax)?;
+
+    if token.eq_ignore_ascii_case("disable") {
+        return Ok(Status::Disable);
+    }
+    let sourcing = if token.eq_ignore_ascii_case("source") {
+        true
+    } else if token.eq_ignore_ascii_case("sink") {
+        false
+    } else {
+        // signed current, negative currents sink
+        let current = parse_current(token).ok_or(ParseErrorKind::InvalidValue)?;
+        return Ok(if current > 0.0 {
+            Status::SourceMicroAmp(current)
+        } else if current < 0.0 {
+            Status::SinkMicroAmp(-current)
+        } else {
+            Status::Disable
+        });
+    };
+
+    let token = tokens.next().ok_or(ParseErrorKind::Syntax)?;
+    Ok(match (sourcing, parse_current(token)) {
+        (_, Some(current)) if current < 0.0 => return Err(ParseErrorKind::InvalidValue),
+        (true, Some(current)) => Status::SourceMicroAmp(current),
+        (false, Some(current)) => Status::SinkMicroAmp(current),
+        (true, None) => Status::Source(token.parse().map_err(|_| ParseErrorKind::InvalidValue)?),
+        (false, None) => Status::Sink(token.parse().map_err(|_| ParseErrorKind::InvalidValue)?),
+    })
+}
+
+/// Parse a current with its `uA` unit, if any.
+fn parse_current(token: &str) -> Option<f32> {
+    let split = token.len().checked_sub(2)?;
+    let (value, unit) = (token.get(..split)?, token.get(split..)?);
+    if !unit.eq_ignore_ascii_case("ua") {
+        return None;
+    }
+    // `f32::from_str` also accepts "NaN" and "inf"
+    value
+        .parse()
+        .ok()
+        .filter(|current: &f32| current.is_finite())
+}
+
+pub(crate) fn parse_duration(
+    token: Option<&str>,
+) -> core::result::Result<MicrosDurationU32, ParseErrorKind> {
+    let token = token.ok_or(ParseErrorKind::Syntax)?;
+    let digits = token
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or(ParseErrorKind::InvalidDuration)?;
+    let value: u32 = token[..digits]
+        .parse()
+        .map_err(|_| ParseErrorKind::InvalidDuration)?;
+    let scale = match &token[digits..] {
+        "us" => 1,
+        "ms" => 1_000,
+        "s" => 1_000_000,
+        _ => return Err(ParseErrorKind::InvalidDuration),
+    };
+    value
+        .checked_mul(scale)
+        .map(MicrosDurationU32::from_ticks)
+        .ok_or(ParseErrorKind::InvalidDuration)
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432",
+        idents(
+            AsyncI2c(sync = "I2c"),
+            AsyncErrorType(sync = "ErrorType"),
+            AsyncDelaySource(sync = "DelaySource")
+        )
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T> AsyncDS4432<I, T> {
+    /// Execute the given steps in order, e.g. a `static` stimulus table
+    pub async fn play<D: AsyncDelaySource<T>>(
+        &mut self,
+        steps: &[Step],
+        delay: &mut D,
+    ) -> Result<(), I::Error> {
+        trace!("play");
+
+        for step in steps {
+            self.play_step(step, delay).await?;
+        }
+        Ok(())
+    }
+
+    /// Parse and execute a text script
+    ///
+    /// The whole script is checked before the first step is executed, so a typo at the end of a
+    /// script doesn't leave the outputs half-way through the stimulus.
+    pub async fn run_script<D: AsyncDelaySource<T>>(
+        &mut self,
+        script: &str,
+        delay: &mut D,
+    ) -> core::result::Result<(), ScriptError<I::Error>> {
+        trace!("run_script");
+
+        for step in Script::new(script) {
+            step?;
+        }
+        for step in Script::new(script).flatten() {
+            self.play_step(&step, delay).await?;
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn play_step<D: AsyncDelaySource<T>>(
+        &mut self,
+        step: &Step,
+        delay: &mut D,
+    ) -> Result<(), I::Error> {
+        match *step {
+            Step::Set { output, status } => self.set_status(output, status).await.map(|_| ()),
+            Step::Wait { duration } => {
+                delay
+                    .provider(&mut self.delay)
+                    .delay_us(duration.ticks())
+                    .await;
+                Ok(())
+            }
+            Step::Ramp {
+                output,
+                target,
+                duration,
+            } => self.ramp_to(output, target, duration, delay).await,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal_mock::eh1::delay::NoopDelay;
+    use embedded_hal_mock::eh1::i2c;
+    use fugit::ExtU32;
+    use std::vec;
+
+    use crate::time::{Clock, MockClock, MockDelay};
+    use crate::SLAVE_ADDRESS;
+
+    #[test]
+    fn parses_all_steps() {
+        let script = "
+            set out0 source 42
+            SET OUT1 sink 88.5uA
+            set out1 -100uA
+            set out0 disable
+            wait 250us
+            ramp out1 to 60uA over 1s
+        ";
+        let steps = [
+            Step::Set {
+                output: Output::Zero,
+                status: Status::Source(42),
+            },
+            Step::Set {
+                output: Output::One,
+                status: Status::SinkMicroAmp(88.5),
+            },
+            Step::Set {
+                output: Output::One,
+                status: Status::SinkMicroAmp(100.0),
+            },
+            Step::Set {
+                output: Output::Zero,
+                status: Status::Disable,
+            },
+            Step::Wait {
+                duration: 250.micros(),
+            },
+            Step::Ramp {
+                output: Output::One,
+                target: Status::SourceMicroAmp(60.0),
+                duration: 1.secs(),
+            },
+        ];
+        assert!(Script::new(script).eq(steps.into_iter().map(Ok)));
+    }
+
+    #[test]
+    fn reports_invalid_lines() {
+        let errors = [
+            ("jump out0", ParseErrorKind::UnknownCommand),
+            ("set out2 disable", ParseErrorKind::InvalidOutput),
+            ("set out0 source lots", ParseErrorKind::InvalidValue),
+            ("wait 10 ms", ParseErrorKind::InvalidDuration),
+            ("ramp out0 -60uA over 1s", ParseErrorKind::Syntax),
+            ("set out0 disable now", ParseErrorKind::Syntax),
+        ];
+        for (line, kind) in errors {
+            assert_eq!(
+                Script::new(line).next(),
+                Some(Err(ParseError { line: 1, kind }))
+            );
+        }
+    }
+
+    #[test]
+    fn run_script_rejects_non_finite_currents() {
+        let mock = i2c::Mock::new(&[]);
+        let mut ds4432 = DS4432::new(mock);
+
+        for script in [
+            "wait 1ms\nset out0 NaNuA",
+            "wait 1ms\nset out0 -NaNuA",
+            "wait 1ms\nset out0 infuA",
+            "wait 1ms\nset out0 -infuA",
+            "wait 1ms\nset out0 sink infuA",
+            "wait 1ms\nramp out0 to NaNuA over 1s",
+        ] {
+            assert_eq!(
+                ds4432.run_script(script, &mut NoopDelay),
+                Err(ScriptError::Parse(ParseError {
+                    line: 2,
+                    kind: ParseErrorKind::InvalidValue
+                }))
+            );
+        }
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[test]
+    fn run_script_checks_before_executing() {
+        let mock = i2c::Mock::new(&[]);
+        let mut ds4432 = DS4432::new(mock);
+
+        assert_eq!(
+            ds4432.run_script("set out0 source 42\nset out0 nowhere", &mut NoopDelay),
+            Err(ScriptError::Parse(ParseError {
+                line: 2,
+                kind: ParseErrorKind::InvalidValue
+            }))
+        );
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[test]
+    fn run_script_drives_outputs() {
+        let expectations = [
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0xAA]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0x00]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::new(mock);
+        let clock = MockClock::new();
+
+        ds4432
+            .run_script(
+                "set out0 source 42\nwait 1ms\nset out0 disable",
+                &mut MockDelay::new(&clock),
+            )
+            .unwrap();
+        assert_eq!(clock.now_us(), 1_000);
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[test]
+    fn plays_static_tables() {
+        use crate::mock::{MockBus, MockDs4432};
+
+        static TABLE: [Step; 3] = [
+            Step::set(Output::Zero, Status::Source(42)),
+            Step::wait(MicrosDurationU32::millis(2)),
+            Step::set(Output::One, Status::Sink(7)),
+        ];
+        let mut ds4432 = MockDs4432::new(MockBus::new());
+        let clock = MockClock::new();
+
+        ds4432.play(&TABLE, &mut MockDelay::new(&clock)).unwrap();
+        assert_eq!(clock.now_us(), 2_000);
+        let bus = ds4432.release();
+        assert_eq!(bus.register(Output::Zero), 0xAA);
+        assert_eq!(bus.register(Output::One), 0x07);
+    }
+}
@@ -0,0 +1,247 @@
+//! Interactive debug shell commands.
+//!
+//! Ready-made commands to poke the DAC from a UART debug console during bring-up. The shell
+//! itself (line editing, echo, e.g. the `menu` crate) stays in the application, which hands the
+//! entered lines to `handle_shell` and prints the response. Outputs and values use the
+//! [`script`](crate::script) syntax:
+//!
+//! - `set <output> <value>` sets the status of an output.
+//! - `get <output>` prints the status of an output.
+//! - `sweep <output> <value> to <value> over <duration>` sets an output then ramps it.
+//! - `dump` prints the register value and status of both outputs.
+//! - `help` prints the list of commands.
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! # use embedded_hal_mock::eh1::{delay::NoopDelay, i2c};
+//! use ds4432::DS4432;
+//!
+//! # let i2c = i2c::Mock::new(&[
+//! #     i2c::Transaction::write(0x48, vec![0xF8, 0xAA]),
+//! #     i2c::Transaction::write_read(0x48, vec![0xF8], vec![0xAA]),
+//! # ]);
+//! let mut ds4432 = DS4432::new(i2c);
+//! let mut response = String::new();
+//!
+//! ds4432.handle_shell("set out0 source 42", &mut response, &mut NoopDelay).unwrap();
+//! ds4432.handle_shell("get out0", &mut response, &mut NoopDelay).unwrap();
+//! assert_eq!(response, "out0 source 42\n");
+//! # ds4432.release().done();
+//! ```
+
+use core::fmt::Write;
+
+#[cfg(feature = "async")]
+use crate::delay::AsyncDelaySource;
+#[cfg(feature = "sync")]
+use crate::delay::DelaySource;
+#[cfg(feature = "sync")]
+use crate::DS4432;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::ErrorType;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+use crate::script::{expect_keyword, parse_duration, parse_output, parse_value, ParseErrorKind};
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+use crate::{Error, Output, Status};
+
+/// The list of commands printed by `help`.
+pub const HELP: &str = "\
+set <output> <value>
+get <output>
+sweep <output> <value> to <value> over <duration>
+dump
+help
+";
+
+/// Write a status using the script value syntax.
+fn write_status<W: Write>(response: &mut W, status: Status) -> core::fmt::Result {
+    match status {
+        Status::Disable => write!(response, "disable"),
+        Status::Sink(code) => write!(response, "sink {}", code),
+        Status::Source(code) => write!(response, "source {}", code),
+        Status::SinkMicroAmp(current) => write!(response, "sink {:.3}uA", current),
+        Status::SourceMicroAmp(current) => write!(response, "source {:.3}uA", current),
+    }
+}
+
+/// Why a shell command can't be executed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum ShellError<E> {
+    /// The command line can't be parsed
+    Syntax(ParseErrorKind),
+    /// The response can't be written
+    Response,
+    /// The driver failed to execute the command
+    Device(Error<E>),
+}
+
+impl<E> From<Error<E>> for ShellError<E> {
+    fn from(error: Error<E>) -> Self {
+        Self::Device(error)
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432",
+        idents(
+            AsyncI2c(sync = "I2c"),
+            AsyncErrorType(sync = "ErrorType"),
+            AsyncDelaySource(sync = "DelaySource")
+        )
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T> AsyncDS4432<I, T> {
+    /// Execute a debug shell command line, writing its output into `response`
+    pub async fn handle_shell<W: Write, D: AsyncDelaySource<T>>(
+        &mut self,
+        line: &str,
+        response: &mut W,
+        delay: &mut D,
+    ) -> Result<(), ShellError<I::Error>> {
+        trace!("handle_shell");
+
+        let mut tokens = line.split_whitespace();
+        let command = match tokens.next() {
+            Some(command) => command,
+            None => return Ok(()),
+        };
+
+        let written = if command.eq_ignore_ascii_case("set") {
+            let output = parse_output(tokens.next()).map_err(ShellError::Syntax)?;
+            let status = parse_value(&mut tokens).map_err(ShellError::Syntax)?;
+            if tokens.next().is_some() {
+                return Err(ShellError::Syntax(ParseErrorKind::Syntax));
+            }
+            self.set_status(output, status).await?;
+            return Ok(());
+        } else if command.eq_ignore_ascii_case("get") {
+            let output = parse_output(tokens.next()).map_err(ShellError::Syntax)?;
+            if tokens.next().is_some() {
+                return Err(ShellError::Syntax(ParseErrorKind::Syntax));
+            }
+            let status = self.status(output).await?;
+            write!(response, "out{} ", output.index())
+                .and_then(|_| write_status(response, status))
+                .and_then(|_| writeln!(response))
+        } else if command.eq_ignore_ascii_case("sweep") {
+            let output = parse_output(tokens.next()).map_err(ShellError::Syntax)?;
+            let start = parse_value(&mut tokens).map_err(ShellError::Syntax)?;
+            expect_keyword(tokens.next(), "to").map_err(ShellError::Syntax)?;
+            let end = parse_value(&mut tokens).map_err(ShellError::Syntax)?;
+            expect_keyword(tokens.next(), "over").map_err(ShellError::Syntax)?;
+            let duration = parse_duration(tokens.next()).map_err(ShellError::Syntax)?;
+            if tokens.next().is_some() {
+                return Err(ShellError::Syntax(ParseErrorKind::Syntax));
+            }
+            self.set_status(output, start).await?;
+            self.ramp_to(output, end, duration, delay).await?;
+            return Ok(());
+        } else if command.eq_ignore_ascii_case("dump") {
+            if tokens.next().is_some() {
+                return Err(ShellError::Syntax(ParseErrorKind::Syntax));
+            }
+            let state = self.dump().await?;
+            for output in Output::ALL {
+                let value = state.register(output);
+                let status = self.decode(output, value);
+                write!(response, "out{} 0x{:02X} ", output.index(), value)
+                    .and_then(|_| write_status(response, status))
+                    .and_then(|_| match self.label(output) {
+                        Some(label) => writeln!(response, " {:?}", label),
+                        None => writeln!(response),
+                    })
+                    .map_err(|_| ShellError::Response)?;
+            }
+            Ok(())
+        } else if command.eq_ignore_ascii_case("help") {
+            response.write_str(HELP)
+        } else {
+            return Err(ShellError::Syntax(ParseErrorKind::UnknownCommand));
+        };
+        written.map_err(|_| ShellError::Response)
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal_mock::eh1::{delay::NoopDelay, i2c};
+    use std::string::String;
+    #[cfg(not(feature = "tiny"))]
+    use std::vec;
+
+    #[cfg(not(feature = "tiny"))]
+    use crate::SLAVE_ADDRESS;
+
+    #[cfg(not(feature = "tiny"))]
+    #[test]
+    fn sweeps_and_dumps() {
+        let expectations = [
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x01]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::One as u8], vec![0x01]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x00]),
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::One as u8, 0x81]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0x00]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::One as u8], vec![0x81]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::with_rfs(mock, None, Some(80_000)).unwrap();
+        ds4432.set_label(Output::One, "VREF");
+        let mut response = String::new();
+
+        ds4432
+            .handle_shell(
+                "sweep out1 sink 1 to source 1 over 2ms",
+                &mut response,
+                &mut NoopDelay,
+            )
+            .unwrap();
+        ds4432
+            .handle_shell("dump", &mut response, &mut NoopDelay)
+            .unwrap();
+        assert_eq!(
+            response,
+            "out0 0x00 disable\nout1 0x81 source 0.779uA \"VREF\"\n"
+        );
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[test]
+    fn rejects_unknown_commands() {
+        let mock = i2c::Mock::new(&[]);
+        let mut ds4432 = DS4432::new(mock);
+        let mut response = String::new();
+
+        assert_eq!(
+            ds4432.handle_shell("poke out0", &mut response, &mut NoopDelay),
+            Err(ShellError::Syntax(ParseErrorKind::UnknownCommand))
+        );
+        assert_eq!(
+            ds4432.handle_shell("get out0 now", &mut response, &mut NoopDelay),
+            Err(ShellError::Syntax(ParseErrorKind::Syntax))
+        );
+        ds4432
+            .handle_shell("help", &mut response, &mut NoopDelay)
+            .unwrap();
+        assert_eq!(response, HELP);
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+}
@@ -0,0 +1,349 @@
+//! Device register snapshots.
+//!
+//! [`DeviceState`] holds the register values of both outputs, read with `dump`. End-of-line
+//! test fixtures compare the device against a golden configuration with `verify_against`, which
+//! reports the outputs whose code or polarity differ.
+//!
+//! [`DeviceState::diff`] lists what changes between two states, in a form suited to logs, and
+//! `apply` reconciles the device with a wanted state, only writing the outputs that change.
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! # use embedded_hal_mock::eh1::i2c;
+//! use ds4432::event::Polarity;
+//! use ds4432::state::{DeviceState, Setting};
+//! use ds4432::{Output, DS4432};
+//!
+//! # let i2c = i2c::Mock::new(&[
+//! #     i2c::Transaction::write_read(0x48, vec![0xF8], vec![0xAA]),
+//! #     i2c::Transaction::write_read(0x48, vec![0xF9], vec![0x10]),
+//! # ]);
+//! let mut ds4432 = DS4432::new(i2c);
+//! let golden = DeviceState::from_registers(0xAA, 0x90);
+//!
+//! let diff = ds4432.verify_against(&golden).unwrap();
+//! let mismatch = diff.mismatch(Output::One).unwrap();
+//! assert_eq!(mismatch.expected, Setting { code: 16, polarity: Polarity::Source });
+//! assert_eq!(mismatch.actual, Setting { code: 16, polarity: Polarity::Sink });
+//! assert!(diff.mismatch(Output::Zero).is_none());
+//! # ds4432.release().done();
+//! ```
+
+use core::fmt;
+
+#[cfg(feature = "sync")]
+use crate::DS4432;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::ErrorType;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+use crate::event::Polarity;
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+use crate::{Config, Output, PerOutput, Result, Scale, Status};
+
+/// The code and current direction of an output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Setting {
+    pub code: u8,
+    pub polarity: Polarity,
+}
+
+/// The register values of both outputs of a device.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct DeviceState {
+    pub(crate) registers: PerOutput<u8>,
+}
+
+impl DeviceState {
+    /// Create a state from the raw register values of each output.
+    pub const fn from_registers(out0: u8, out1: u8) -> Self {
+        Self {
+            registers: PerOutput::new(out0, out1),
+        }
+    }
+
+    /// Get the raw register value of an output.
+    pub fn register(&self, output: Output) -> u8 {
+        self.registers[output]
+    }
+
+    /// Get the code and current direction of an output.
+    pub fn setting(&self, output: Output) -> Setting {
+        let value = self.registers[output];
+        Setting {
+            code: value & 0x7F,
+            polarity: if value & 0x80 == 0x80 {
+                Polarity::Source
+            } else {
+                Polarity::Sink
+            },
+        }
+    }
+
+    /// Get the raw code status of an output.
+    pub fn status(&self, output: Output) -> Status {
+        self.registers[output].into()
+    }
+
+    /// List the outputs changing from this state to the new one.
+    pub fn diff(&self, new: &DeviceState) -> StateChanges {
+        let mut changes = StateChanges::default();
+        for output in Output::ALL {
+            if self.register(output) != new.register(output) {
+                changes.changes[output] = Some(Change {
+                    output,
+                    old: self.setting(output),
+                    new: new.setting(output),
+                    old_ua: None,
+                    new_ua: None,
+                });
+            }
+        }
+        changes
+    }
+}
+
+/// An output whose setting differs from the expected one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Mismatch {
+    pub expected: Setting,
+    pub actual: Setting,
+}
+
+/// The outputs of a device differing from an expected state.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct StateDiff {
+    mismatches: PerOutput<Option<Mismatch>>,
+}
+
+impl StateDiff {
+    /// Compare an actual state with the expected one.
+    fn new(expected: &DeviceState, actual: &DeviceState) -> Self {
+        let mut diff = Self::default();
+        for output in Output::ALL {
+            if expected.register(output) != actual.register(output) {
+                diff.mismatches[output] = Some(Mismatch {
+                    expected: expected.setting(output),
+                    actual: actual.setting(output),
+                });
+            }
+        }
+        diff
+    }
+
+    /// Whether both outputs match.
+    pub fn is_empty(&self) -> bool {
+        self.mismatches
+            .iter()
+            .all(|(_, mismatch)| mismatch.is_none())
+    }
+
+    /// Get the mismatch of an output, if any.
+    pub fn mismatch(&self, output: Output) -> Option<Mismatch> {
+        self.mismatches[output]
+    }
+
+    /// Iterate over the mismatching outputs.
+    pub fn iter(&self) -> impl Iterator<Item = (Output, Mismatch)> + '_ {
+        self.mismatches
+            .iter()
+            .filter_map(|(output, mismatch)| mismatch.map(|mismatch| (output, mismatch)))
+    }
+}
+
+/// The change of an output between two states.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Change {
+    pub output: Output,
+    pub old: Setting,
+    pub new: Setting,
+    /// The old current in microamps, if the Rfs is known
+    pub old_ua: Option<f32>,
+    /// The new current in microamps, if the Rfs is known
+    pub new_ua: Option<f32>,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let polarity = |setting: Setting| match setting.polarity {
+            Polarity::Sink => "sink",
+            Polarity::Source => "source",
+        };
+        write!(
+            f,
+            "channel={} code={}->{} polarity={}->{}",
+            self.output.index(),
+            self.old.code,
+            self.new.code,
+            polarity(self.old),
+            polarity(self.new)
+        )?;
+        #[cfg(not(feature = "tiny"))]
+        if let (Some(old), Some(new)) = (self.old_ua, self.new_ua) {
+            write!(f, " current_ua={:.3}->{:.3}", old, new)?;
+        }
+        Ok(())
+    }
+}
+
+/// The outputs changing between two device states.
+///
+/// Displayed as the `key=old->new` pairs of each change, e.g.
+/// `channel=0 code=42->10 polarity=sink->source current_ua=32.714->7.789`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct StateChanges {
+    changes: PerOutput<Option<Change>>,
+}
+
+impl StateChanges {
+    /// Add the currents in microamps of the outputs whose Rfs is known.
+    pub fn with_config(mut self, config: &Config) -> Self {
+        for output in Output::ALL {
+            if let (Some(change), Some(rfs)) = (&mut self.changes[output], config.rfs_ohm(output)) {
+                let scale = Scale::new(rfs);
+                change.old_ua = Some(scale.current_ua(change.old.code));
+                change.new_ua = Some(scale.current_ua(change.new.code));
+            }
+        }
+        self
+    }
+
+    /// Whether no output changes.
+    pub fn is_empty(&self) -> bool {
+        self.changes.iter().all(|(_, change)| change.is_none())
+    }
+
+    /// Get the change of an output, if any.
+    pub fn change(&self, output: Output) -> Option<Change> {
+        self.changes[output]
+    }
+
+    /// Iterate over the changing outputs.
+    pub fn iter(&self) -> impl Iterator<Item = Change> + '_ {
+        self.changes.iter().filter_map(|(_, change)| *change)
+    }
+}
+
+impl fmt::Display for StateChanges {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("unchanged");
+        }
+        for (index, change) in self.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432",
+        idents(AsyncI2c(sync = "I2c"), AsyncErrorType(sync = "ErrorType"))
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T> AsyncDS4432<I, T> {
+    /// Read the register values of both outputs
+    pub async fn dump(&mut self) -> Result<DeviceState, I::Error> {
+        trace!("dump");
+
+        let mut state = DeviceState::default();
+        for output in Output::ALL {
+            state.registers[output] = self.read_register(output).await?;
+        }
+        Ok(state)
+    }
+
+    /// Read the device and compare it with the expected state, e.g. a golden configuration
+    pub async fn verify_against(&mut self, expected: &DeviceState) -> Result<StateDiff, I::Error> {
+        trace!("verify_against");
+
+        let actual = self.dump().await?;
+        Ok(StateDiff::new(expected, &actual))
+    }
+
+    /// Bring the device to the wanted state, only writing the outputs that change, and return
+    /// the changes
+    pub async fn apply(&mut self, wanted: &DeviceState) -> Result<StateChanges, I::Error> {
+        trace!("apply");
+
+        let changes = self.dump().await?.diff(wanted).with_config(&self.config);
+        for change in changes.iter() {
+            self.write_register(change.output, wanted.register(change.output))
+                .await?;
+        }
+        debug!("applied {}", changes);
+        Ok(changes)
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal_mock::eh1::i2c;
+    use std::vec;
+
+    use crate::SLAVE_ADDRESS;
+
+    #[test]
+    fn matches_golden_state() {
+        let expectations = [
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0x00]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::One as u8], vec![0x85]),
+        ];
+        let mock = i2c::Mock::new(&expectations);
+        let mut ds4432 = DS4432::new(mock);
+
+        let diff = ds4432
+            .verify_against(&DeviceState::from_registers(0x00, 0x85))
+            .unwrap();
+        assert!(diff.is_empty());
+        assert_eq!(diff.iter().count(), 0);
+
+        let mut mock = ds4432.release();
+        mock.done();
+    }
+
+    #[cfg(not(feature = "tiny"))]
+    #[test]
+    fn applies_only_the_changes() {
+        use crate::mock::{MockBus, MockDs4432};
+        use std::string::ToString;
+
+        let mut ds4432 = MockDs4432::with_rfs(MockBus::new(), Some(80_000), None).unwrap();
+        ds4432.set_status(Output::One, Status::Source(5)).unwrap();
+
+        let changes = ds4432
+            .apply(&DeviceState::from_registers(0x2A, 0x85))
+            .unwrap();
+        assert!(changes.change(Output::One).is_none());
+        assert_eq!(
+            changes.to_string(),
+            "channel=0 code=0->42 polarity=sink->sink current_ua=0.000->32.714"
+        );
+        assert_eq!(ds4432.release().register(Output::Zero), 0x2A);
+        assert!(DeviceState::default()
+            .diff(&DeviceState::default())
+            .is_empty());
+    }
+}
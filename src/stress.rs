@@ -0,0 +1,164 @@
+//! Pseudo-random stress test.
+//!
+//! `stress_test` writes pseudo-random register values to both outputs and reads each one back,
+//! for manufacturing and soak testing. The sequence only depends on the seed, so a failing run
+//! can be reproduced. Failures don't stop the test, they are summed up in a [`StressReport`].
+//!
+//! The outputs are left with the last random values.
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! # #[cfg(feature = "mock")] {
+//! use ds4432::mock::{MockBus, MockDs4432};
+//!
+//! let mut ds4432 = MockDs4432::new(MockBus::new());
+//!
+//! let report = ds4432.stress_test(1_000, 0xC0FFEE);
+//! assert!(report.passed());
+//! assert_eq!(report.checks, 2_000);
+//! # }
+//! ```
+
+#[cfg(feature = "sync")]
+use crate::DS4432;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::ErrorType;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+use crate::derating::clamp_register;
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+use crate::{Output, PerOutput};
+
+/// A register value read back differently than written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct StressFailure {
+    pub iteration: u32,
+    pub output: Output,
+    pub expected: u8,
+    pub actual: u8,
+}
+
+/// The outcome of a stress test.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct StressReport {
+    /// Number of write and readback checks done
+    pub checks: u32,
+    /// Number of values read back differently than written, per output
+    pub mismatches: PerOutput<u32>,
+    /// Number of checks failing on a bus error, per output
+    pub bus_errors: PerOutput<u32>,
+    /// The first mismatch, to reproduce it
+    pub first_mismatch: Option<StressFailure>,
+}
+
+impl StressReport {
+    /// Whether every check passed.
+    pub fn passed(&self) -> bool {
+        Output::ALL
+            .into_iter()
+            .all(|output| self.mismatches[output] == 0 && self.bus_errors[output] == 0)
+    }
+}
+
+/// A xorshift32 pseudo-random generator.
+struct XorShift(u32);
+
+impl XorShift {
+    fn new(seed: u32) -> Self {
+        // the generator is stuck at 0
+        Self(if seed == 0 { 0x9E37_79B9 } else { seed })
+    }
+
+    fn next(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432",
+        idents(AsyncI2c(sync = "I2c"), AsyncErrorType(sync = "ErrorType"))
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T> AsyncDS4432<I, T> {
+    /// Write `iterations` pseudo-random values generated from `seed` to both outputs, reading
+    /// each one back
+    pub async fn stress_test(&mut self, iterations: u32, seed: u32) -> StressReport {
+        trace!("stress_test");
+
+        let mut report = StressReport::default();
+        let mut rng = XorShift::new(seed);
+        for iteration in 0..iterations {
+            let values = rng.next().to_le_bytes();
+            for output in Output::ALL {
+                let expected = clamp_register(values[output.index()], self.config.max_code());
+                report.checks += 1;
+                if self.write_register(output, expected).await.is_err() {
+                    report.bus_errors[output] += 1;
+                    continue;
+                }
+                match self.read_register(output).await {
+                    Ok(actual) if actual != expected => {
+                        report.mismatches[output] += 1;
+                        report.first_mismatch.get_or_insert(StressFailure {
+                            iteration,
+                            output,
+                            expected,
+                            actual,
+                        });
+                    }
+                    Ok(_) => {}
+                    Err(_) => report.bus_errors[output] += 1,
+                }
+            }
+        }
+        report
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    use super::*;
+
+    use crate::derating::DeratingCurve;
+    use crate::mock::{MockBus, MockDs4432};
+
+    #[test]
+    fn sequence_is_reproducible() {
+        let mut first = MockDs4432::new(MockBus::new());
+        let mut second = MockDs4432::new(MockBus::new());
+        assert!(first.stress_test(100, 42).passed());
+        assert!(second.stress_test(100, 42).passed());
+        let (first, second) = (first.release(), second.release());
+        assert_eq!(first.register(Output::Zero), second.register(Output::Zero));
+        assert_eq!(first.register(Output::One), second.register(Output::One));
+
+        let mut bus = MockBus::new();
+        bus.set_failing(true);
+        let report = MockDs4432::new(bus).stress_test(3, 42);
+        assert!(!report.passed());
+        assert_eq!(report.bus_errors, PerOutput::new(3, 3));
+    }
+
+    #[test]
+    fn expects_the_derated_codes() {
+        let mut ds4432 = MockDs4432::new(MockBus::new());
+        ds4432.set_derating(Some(DeratingCurve::new(&[(25, 127), (75, 27)])));
+        ds4432.set_temperature(75.0).unwrap();
+        assert!(ds4432.stress_test(100, 42).passed());
+    }
+}
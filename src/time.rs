@@ -0,0 +1,132 @@
+//! Time abstractions.
+//!
+//! Time-based features (ramps, script playback, pacing, ...) only rely on a delay provider
+//! implementing `DelayNs`, and on a [`Clock`] when they need to know the current time. The
+//! [`MockClock`] and [`MockDelay`] implementations make them deterministic in host-side tests:
+//! waiting on the delay moves the clock forward instantly.
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! # use embedded_hal_mock::eh1::i2c;
+//! use ds4432::time::{Clock, MockClock, MockDelay};
+//! use ds4432::{Output, Status, DS4432};
+//! use fugit::ExtU32;
+//!
+//! # let i2c = i2c::Mock::new(&[
+//! #     i2c::Transaction::write_read(0x48, vec![0xF8], vec![0x00]),
+//! #     i2c::Transaction::write(0x48, vec![0xF8, 0x01]),
+//! #     i2c::Transaction::write(0x48, vec![0xF8, 0x02]),
+//! # ]);
+//! let clock = MockClock::new();
+//! let mut delay = MockDelay::new(&clock);
+//! let mut ds4432 = DS4432::new(i2c);
+//!
+//! ds4432
+//!     .ramp_to(Output::Zero, Status::Sink(2), 10.millis(), &mut delay)
+//!     .unwrap();
+//! assert_eq!(clock.now_us(), 10_000);
+//! # ds4432.release().done();
+//! ```
+
+use core::cell::Cell;
+
+use fugit::NanosDurationU64;
+
+/// A monotonic source of time.
+pub trait Clock {
+    /// The current time in microseconds.
+    fn now_us(&self) -> u64;
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now_us(&self) -> u64 {
+        (**self).now_us()
+    }
+}
+
+/// A clock given to a driver, compared by address.
+#[derive(Clone, Copy)]
+pub(crate) struct InstalledClock(pub(crate) &'static (dyn Clock + Sync));
+
+impl PartialEq for InstalledClock {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(
+            self.0 as *const _ as *const (),
+            other.0 as *const _ as *const (),
+        )
+    }
+}
+
+impl Eq for InstalledClock {}
+
+impl core::fmt::Debug for InstalledClock {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Clock({:p})", self.0 as *const _ as *const ())
+    }
+}
+
+#[cfg(feature = "defmt-03")]
+impl defmt::Format for InstalledClock {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "Clock")
+    }
+}
+
+/// A clock only moving forward when told to, or when a [`MockDelay`] waits on it.
+#[derive(Debug, Default)]
+pub struct MockClock {
+    now_ns: Cell<u64>,
+}
+
+impl MockClock {
+    /// Create a clock starting at 0.
+    pub const fn new() -> Self {
+        Self {
+            now_ns: Cell::new(0),
+        }
+    }
+
+    /// Move the clock forward.
+    pub fn advance(&self, duration: NanosDurationU64) {
+        self.now_ns.set(self.now_ns.get() + duration.ticks());
+    }
+
+    /// The current time in nanoseconds.
+    pub fn now_ns(&self) -> u64 {
+        self.now_ns.get()
+    }
+}
+
+impl Clock for MockClock {
+    fn now_us(&self) -> u64 {
+        self.now_ns.get() / 1_000
+    }
+}
+
+/// A delay provider returning immediately after moving a [`MockClock`] forward.
+#[derive(Debug, Clone, Copy)]
+pub struct MockDelay<'a> {
+    clock: &'a MockClock,
+}
+
+impl<'a> MockDelay<'a> {
+    /// Create a delay provider moving the given clock.
+    pub fn new(clock: &'a MockClock) -> Self {
+        Self { clock }
+    }
+}
+
+#[cfg(feature = "sync")]
+impl embedded_hal::delay::DelayNs for MockDelay<'_> {
+    fn delay_ns(&mut self, ns: u32) {
+        self.clock.advance(NanosDurationU64::from_ticks(ns as u64));
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::delay::DelayNs for MockDelay<'_> {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.clock.advance(NanosDurationU64::from_ticks(ns as u64));
+    }
+}
@@ -0,0 +1,178 @@
+//! Async adapter for blocking I2C buses on Linux hosts.
+//!
+//! Host-side buses (e.g. `linux_embedded_hal::I2cdev` on a Raspberry Pi or a lab gateway) are
+//! blocking. [`BlockingI2c`] runs their transactions on the tokio blocking thread pool so the
+//! [`AsyncDS4432`](crate::AsyncDS4432) API can be used from async test frameworks.
+//!
+//! # Example
+//! ```ignore
+//! use ds4432::tokio::BlockingI2c;
+//! use ds4432::{AsyncDS4432, Output, Status};
+//! use linux_embedded_hal::I2cdev;
+//!
+//! let i2c = BlockingI2c::new(I2cdev::new("/dev/i2c-1")?);
+//! let mut ds4432 = AsyncDS4432::new(i2c);
+//! ds4432.set_status(Output::Zero, Status::Source(42)).await?;
+//! ```
+
+use std::sync::{Arc, Mutex, PoisonError};
+use std::vec::Vec;
+
+use embedded_hal::i2c::{I2c, Operation};
+use embedded_hal_async::i2c::{ErrorType, I2c as AsyncI2c, Operation as AsyncOperation};
+
+/// A blocking I2C bus `I` usable as an async one from a tokio runtime.
+#[derive(Debug)]
+pub struct BlockingI2c<I> {
+    inner: Arc<Mutex<I>>,
+}
+
+impl<I> BlockingI2c<I> {
+    /// Wrap a blocking I2C bus.
+    pub fn new(i2c: I) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(i2c)),
+        }
+    }
+
+    /// Return the underlying I2C bus, or `None` if a cancelled transaction is still running on
+    /// the blocking thread pool.
+    pub fn into_inner(self) -> Option<I> {
+        Arc::try_unwrap(self.inner)
+            .ok()
+            .map(|i2c| i2c.into_inner().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+impl<I: I2c + Send + 'static> BlockingI2c<I>
+where
+    I::Error: Send + 'static,
+{
+    /// Run a closure with the bus on the blocking thread pool.
+    async fn run<R: Send + 'static>(&self, f: impl FnOnce(&mut I) -> R + Send + 'static) -> R {
+        let inner = self.inner.clone();
+        let task = ::tokio::task::spawn_blocking(move || {
+            f(&mut inner.lock().unwrap_or_else(PoisonError::into_inner))
+        });
+        match task.await {
+            Ok(result) => result,
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(e) => ::std::panic!("blocking I2C task failed: {e}"),
+        }
+    }
+}
+
+impl<I: I2c> ErrorType for BlockingI2c<I> {
+    type Error = I::Error;
+}
+
+/// An I2C operation owning its buffer, so it can be moved to the blocking thread pool.
+enum OwnedOperation {
+    Read(Vec<u8>),
+    Write(Vec<u8>),
+}
+
+impl<I: I2c + Send + 'static> AsyncI2c for BlockingI2c<I>
+where
+    I::Error: Send + 'static,
+{
+    async fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        let mut buf = std::vec![0; read.len()];
+        let (result, buf) = self
+            .run(move |i2c| (i2c.read(address, &mut buf), buf))
+            .await;
+        read.copy_from_slice(&buf);
+        result
+    }
+
+    async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        let buf = write.to_vec();
+        self.run(move |i2c| i2c.write(address, &buf)).await
+    }
+
+    async fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let write = write.to_vec();
+        let mut buf = std::vec![0; read.len()];
+        let (result, buf) = self
+            .run(move |i2c| (i2c.write_read(address, &write, &mut buf), buf))
+            .await;
+        read.copy_from_slice(&buf);
+        result
+    }
+
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [AsyncOperation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut owned: Vec<OwnedOperation> = operations
+            .iter()
+            .map(|operation| match operation {
+                AsyncOperation::Read(read) => OwnedOperation::Read(std::vec![0; read.len()]),
+                AsyncOperation::Write(write) => OwnedOperation::Write(write.to_vec()),
+            })
+            .collect();
+
+        let (result, owned) = self
+            .run(move |i2c| {
+                let mut borrowed: Vec<Operation<'_>> = owned
+                    .iter_mut()
+                    .map(|operation| match operation {
+                        OwnedOperation::Read(buf) => Operation::Read(buf),
+                        OwnedOperation::Write(buf) => Operation::Write(buf),
+                    })
+                    .collect();
+                let result = i2c.transaction(address, &mut borrowed);
+                drop(borrowed);
+                (result, owned)
+            })
+            .await;
+
+        for (operation, owned) in operations.iter_mut().zip(owned) {
+            if let (AsyncOperation::Read(read), OwnedOperation::Read(buf)) = (operation, owned) {
+                read.copy_from_slice(&buf);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use embedded_hal_mock::eh1::i2c;
+    use std::vec;
+
+    use crate::{AsyncDS4432, Output, Status, SLAVE_ADDRESS};
+
+    #[test]
+    fn drives_async_api_with_blocking_bus() {
+        let expectations = [
+            i2c::Transaction::write(SLAVE_ADDRESS, vec![Output::Zero as u8, 0xAA]),
+            i2c::Transaction::write_read(SLAVE_ADDRESS, vec![Output::Zero as u8], vec![0xAA]),
+        ];
+        let runtime = ::tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let mut ds4432 = AsyncDS4432::new(BlockingI2c::new(i2c::Mock::new(&expectations)));
+
+        runtime.block_on(async {
+            ds4432
+                .set_status(Output::Zero, Status::Source(42))
+                .await
+                .unwrap();
+            assert_eq!(
+                ds4432.status(Output::Zero).await.unwrap(),
+                Status::Source(42)
+            );
+        });
+
+        let mut mock = ds4432.release().into_inner().unwrap();
+        mock.done();
+    }
+}
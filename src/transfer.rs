@@ -0,0 +1,229 @@
+//! Transfer functions.
+//!
+//! A [`TransferFn`] models what an output drives, e.g. an external op-amp stage or a diode
+//! network, by mapping the signed DAC code (sink being negative) to a physical quantity. Once
+//! installed on an output with `set_transfer_fn`, the output is driven in that quantity with
+//! `set_quantity`: the driver inverts the function, clamping to the reachable range, and returns
+//! the quantity actually applied.
+//!
+//! Only `quantity` has to be implemented, the inversion being a bisection over the codes which
+//! requires a monotonic function. Plain `fn(i16) -> f32` functions are transfer functions.
+//!
+//! # Example
+#![cfg_attr(feature = "sync", doc = "```")]
+#![cfg_attr(not(feature = "sync"), doc = "```ignore")]
+//! # use embedded_hal_mock::eh1::i2c;
+//! use ds4432::transfer::Affine;
+//! use ds4432::{Output, DS4432};
+//!
+//! // an inverting stage giving 2.5V at code 0, 20mV per code
+//! static STAGE: Affine = Affine {
+//!     gain: -0.020,
+//!     offset: 2.5,
+//! };
+//!
+//! # let i2c = i2c::Mock::new(&[i2c::Transaction::write(0x48, vec![0xF8, 0xB2])]);
+//! let mut ds4432 = DS4432::new(i2c);
+//! ds4432.set_transfer_fn(Output::Zero, Some(&STAGE));
+//!
+//! // source code 50
+//! assert_eq!(ds4432.set_quantity(Output::Zero, 1.5).unwrap(), 1.5);
+//! # ds4432.release().done();
+//! ```
+
+#[cfg(feature = "sync")]
+use crate::DS4432;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::ErrorType;
+#[cfg(feature = "sync")]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::ErrorType as AsyncErrorType;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+#[cfg(feature = "async")]
+use crate::AsyncDS4432;
+use crate::{derating, register_to_signed, signed_to_register, Error, Output, Result};
+
+/// A monotonic mapping between the signed DAC code of an output and a physical quantity.
+pub trait TransferFn {
+    /// Get the quantity given by a signed code, from -127 (full-scale sink) to 127.
+    fn quantity(&self, code: i16) -> f32;
+
+    /// Get the signed code giving the nearest quantity, clamped to the reachable range.
+    fn code(&self, quantity: f32) -> i16 {
+        let increasing = self.quantity(127) >= self.quantity(-127);
+        let (mut low, mut high) = (-127, 127);
+        while high - low > 1 {
+            let middle = (low + high) / 2;
+            if (self.quantity(middle) > quantity) == increasing {
+                high = middle;
+            } else {
+                low = middle;
+            }
+        }
+        let distance = |code| {
+            let distance = self.quantity(code) - quantity;
+            if distance < 0.0 {
+                -distance
+            } else {
+                distance
+            }
+        };
+        if distance(low) <= distance(high) {
+            low
+        } else {
+            high
+        }
+    }
+}
+
+impl TransferFn for fn(i16) -> f32 {
+    fn quantity(&self, code: i16) -> f32 {
+        self(code)
+    }
+}
+
+/// A transfer function installed on an output, compared by address.
+#[derive(Clone, Copy)]
+pub(crate) struct Installed(pub(crate) &'static (dyn TransferFn + Sync));
+
+impl PartialEq for Installed {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(
+            self.0 as *const _ as *const (),
+            other.0 as *const _ as *const (),
+        )
+    }
+}
+
+impl Eq for Installed {}
+
+impl core::fmt::Debug for Installed {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "TransferFn({:p})", self.0 as *const _ as *const ())
+    }
+}
+
+#[cfg(feature = "defmt-03")]
+impl defmt::Format for Installed {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "TransferFn")
+    }
+}
+
+/// A linear transfer function, `gain * code + offset`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Affine {
+    pub gain: f32,
+    pub offset: f32,
+}
+
+impl TransferFn for Affine {
+    fn quantity(&self, code: i16) -> f32 {
+        self.gain * code as f32 + self.offset
+    }
+
+    fn code(&self, quantity: f32) -> i16 {
+        let code = (quantity - self.offset) / self.gain;
+        // `as i16` saturates, NaN being 0
+        let code = if code < 0.0 { code - 0.5 } else { code + 0.5 } as i16;
+        code.clamp(-127, 127)
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "sync",
+        self = "DS4432",
+        idents(AsyncI2c(sync = "I2c"), AsyncErrorType(sync = "ErrorType"))
+    ),
+    async(feature = "async", keep_self)
+)]
+impl<I: AsyncI2c + AsyncErrorType, T> AsyncDS4432<I, T> {
+    /// Install the transfer function of an output, or remove it
+    pub fn set_transfer_fn(
+        &mut self,
+        output: Output,
+        transfer: Option<&'static (dyn TransferFn + Sync)>,
+    ) {
+        self.config.transfers[output] = transfer.map(Installed);
+    }
+
+    /// Drive an output to the given quantity of its transfer function, returning the quantity
+    /// actually applied
+    pub async fn set_quantity(&mut self, output: Output, quantity: f32) -> Result<f32, I::Error> {
+        trace!("set_quantity");
+
+        let Installed(transfer) =
+            self.config.transfers[output].ok_or(Error::UnknownTransferFn { output })?;
+        let code = transfer.code(quantity).clamp(-127, 127);
+        let value = derating::clamp_register(signed_to_register(code), self.config.max_code());
+        self.write_register(output, value).await?;
+        Ok(transfer.quantity(register_to_signed(value)))
+    }
+
+    /// Get the quantity of the transfer function an output is driven to
+    pub async fn quantity(&mut self, output: Output) -> Result<f32, I::Error> {
+        trace!("quantity");
+
+        let Installed(transfer) =
+            self.config.transfers[output].ok_or(Error::UnknownTransferFn { output })?;
+        let value = self.read_register(output).await?;
+        Ok(transfer.quantity(register_to_signed(value)))
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    use super::*;
+
+    use crate::mock::{MockBus, MockDs4432};
+
+    /// A square law, e.g. the power in a heater.
+    fn square(code: i16) -> f32 {
+        let code = code as f32 + 127.0;
+        code * code / 100.0
+    }
+
+    static SQUARE: fn(i16) -> f32 = square;
+
+    #[test]
+    fn inverts_nonlinear_functions() {
+        let mut ds4432 = MockDs4432::new(MockBus::new());
+        assert_eq!(
+            ds4432.set_quantity(Output::One, 1.0),
+            Err(Error::UnknownTransferFn {
+                output: Output::One
+            })
+        );
+
+        ds4432.set_transfer_fn(Output::One, Some(&SQUARE));
+        // code -117 gives 1.0
+        assert_eq!(ds4432.set_quantity(Output::One, 1.1).unwrap(), 1.0);
+        assert_eq!(ds4432.quantity(Output::One).unwrap(), 1.0);
+        // clamped to full-scale source
+        assert_eq!(ds4432.set_quantity(Output::One, 1e6).unwrap(), 645.16);
+        assert_eq!(
+            Affine {
+                gain: 2.0,
+                offset: 0.0
+            }
+            .code(-1e9),
+            -127
+        );
+    }
+
+    #[test]
+    fn keeps_transfer_fns_across_parts() {
+        let mut ds4432 = MockDs4432::new(MockBus::new());
+        ds4432.set_transfer_fn(Output::One, Some(&SQUARE));
+
+        let (bus, config) = ds4432.into_parts();
+        assert!(config.transfer_fn(Output::Zero).is_none());
+        let mut ds4432 = MockDs4432::from_parts(bus, config);
+        assert_eq!(ds4432.set_quantity(Output::One, 1.1).unwrap(), 1.0);
+    }
+}